@@ -1,11 +1,26 @@
-use std::mem::transmute;
+use std::fs::File;
+use std::path::PathBuf;
 
 use anyhow::Context;
+use memmap2::Mmap;
+use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
+use pyo3::types::PyBytes;
 
 use crate::Testrun;
 
-use super::{TestAnalytics, TestAnalyticsWriter};
+use super::{Aggregation, HistoricTestData, Test, TestAnalytics, TestAnalyticsWriter};
+
+impl<'py> FromPyObject<'py> for Aggregation {
+    fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
+        let s = ob.extract::<&str>()?;
+        match s {
+            "parameter_set" => Ok(Aggregation::PerParameterSet),
+            "base_test" => Ok(Aggregation::PerBaseTest),
+            _ => Err(PyValueError::new_err(format!("Invalid aggregation: {}", s))),
+        }
+    }
+}
 
 #[pyclass]
 pub struct BinaryFormatWriter {
@@ -31,6 +46,29 @@ impl BinaryFormatWriter {
         })
     }
 
+    #[staticmethod]
+    pub fn merge_all(buffers: Vec<Bound<'_, PyBytes>>) -> anyhow::Result<Self> {
+        let formats = buffers
+            .iter()
+            .map(|buffer| TestAnalytics::parse(buffer.as_bytes(), 0))
+            .collect::<Result<Vec<_>, _>>()?;
+        let writer = TestAnalyticsWriter::merge_all(&formats)?;
+        Ok(Self {
+            writer: Some(writer),
+        })
+    }
+
+    /// Sets whether the testruns of parameterized tests are aggregated per
+    /// `"parameter_set"`, or per `"base_test"`.
+    pub fn set_aggregation(&mut self, aggregation: Aggregation) -> anyhow::Result<()> {
+        let writer = self
+            .writer
+            .as_mut()
+            .context("writer was already serialized")?;
+        writer.set_aggregation(aggregation);
+        Ok(())
+    }
+
     pub fn add_testruns(
         &mut self,
         timestamp: u32,
@@ -61,6 +99,26 @@ impl BinaryFormatWriter {
         Ok(())
     }
 
+    pub fn import_historic_json(&mut self, buffer: &[u8]) -> anyhow::Result<()> {
+        let historic_data = HistoricTestData::from_json(buffer)?;
+        let writer = self
+            .writer
+            .as_mut()
+            .context("writer was already serialized")?;
+        writer.import_historic(&historic_data);
+        Ok(())
+    }
+
+    pub fn import_historic_csv(&mut self, buffer: &[u8]) -> anyhow::Result<()> {
+        let historic_data = HistoricTestData::from_csv(buffer)?;
+        let writer = self
+            .writer
+            .as_mut()
+            .context("writer was already serialized")?;
+        writer.import_historic(&historic_data);
+        Ok(())
+    }
+
     pub fn serialize(&mut self) -> anyhow::Result<Vec<u8>> {
         let writer = self
             .writer
@@ -72,10 +130,31 @@ impl BinaryFormatWriter {
     }
 }
 
+/// The buffer backing an [`AggregationReader`].
+enum ReaderBuffer {
+    /// A Python `bytes` object, which is immutable and thus read without copying.
+    Bytes(Py<PyBytes>),
+    /// A memory-mapped file.
+    Mmap(Mmap),
+}
+
+impl ReaderBuffer {
+    fn as_bytes<'a>(&'a self, py: Python<'a>) -> &'a [u8] {
+        match self {
+            ReaderBuffer::Bytes(bytes) => bytes.as_bytes(py),
+            ReaderBuffer::Mmap(mmap) => mmap,
+        }
+    }
+}
+
+/// Reads aggregated test data out of a serialized [`TestAnalytics`] file.
+///
+/// Instead of holding onto a parsed [`TestAnalytics`], which would borrow from
+/// the buffer owned by this same struct, the (cheap) parsing is repeated on each query.
 #[pyclass]
 pub struct AggregationReader {
-    _buffer: Vec<u8>,
-    format: TestAnalytics<'static>,
+    buffer: ReaderBuffer,
+    timestamp: u32,
 }
 
 #[pyclass(get_all)]
@@ -85,6 +164,8 @@ pub struct TestAggregate {
     pub test_id: String,
 
     pub testsuite: Option<String>,
+    pub classname: String,
+    pub computed_name: String,
     pub flags: Vec<String>,
 
     pub failure_rate: f32,
@@ -105,24 +186,54 @@ pub struct TestAggregate {
     pub last_duration: f32,
 }
 
+impl AggregationReader {
+    fn from_buffer(py: Python<'_>, buffer: ReaderBuffer, timestamp: u32) -> anyhow::Result<Self> {
+        // validate the buffer upfront, so that errors surface when creating the reader
+        TestAnalytics::parse(buffer.as_bytes(py), timestamp)?;
+
+        Ok(Self { buffer, timestamp })
+    }
+}
+
 #[pymethods]
 impl AggregationReader {
     #[new]
-    pub fn new(buffer: Vec<u8>, timestamp: u32) -> anyhow::Result<Self> {
-        let format = TestAnalytics::parse(&buffer, timestamp)?;
-        // SAFETY: the lifetime of `TestAnalytics` depends on `buffer`,
-        // which we do not mutate, and which outlives the parsed format.
-        let format = unsafe { transmute::<TestAnalytics<'_>, TestAnalytics<'_>>(format) };
+    pub fn new(buffer: &Bound<'_, PyAny>, timestamp: u32) -> anyhow::Result<Self> {
+        let py = buffer.py();
+        let buffer = match buffer.downcast::<PyBytes>() {
+            Ok(bytes) => ReaderBuffer::Bytes(bytes.clone().unbind()),
+            // other buffers, like a `bytearray` or `memoryview`, may be mutated or resized
+            // from Python while we read them, so they are copied into a new `bytes` object
+            Err(_) => {
+                let bytes = py.get_type::<PyBytes>().call1((buffer,))?;
+                ReaderBuffer::Bytes(
+                    bytes
+                        .downcast_into::<PyBytes>()
+                        .map_err(PyErr::from)?
+                        .unbind(),
+                )
+            }
+        };
+        Self::from_buffer(py, buffer, timestamp)
+    }
 
-        Ok(Self {
-            _buffer: buffer,
-            format,
-        })
+    /// Opens the file at `path` by memory-mapping it.
+    #[staticmethod]
+    pub fn open(py: Python<'_>, path: PathBuf, timestamp: u32) -> anyhow::Result<Self> {
+        let file =
+            File::open(&path).with_context(|| format!("could not open {}", path.display()))?;
+        // SAFETY: the file is opened read-only, and we assume that it is not being
+        // modified by anyone else while it is mapped.
+        let mmap = unsafe { Mmap::map(&file) }
+            .with_context(|| format!("could not map {}", path.display()))?;
+
+        Self::from_buffer(py, ReaderBuffer::Mmap(mmap), timestamp)
     }
 
     #[pyo3(signature = (interval_start, interval_end, flags=None))]
     pub fn get_test_aggregates(
         &self,
+        py: Python<'_>,
         interval_start: usize,
         interval_end: usize,
         flags: Option<Vec<String>>,
@@ -132,30 +243,63 @@ impl AggregationReader {
             .map(|flags| flags.iter().map(|flag| flag.as_str()).collect());
         let desired_range = interval_start..interval_end;
 
-        let tests = self.format.tests(desired_range, flags.as_deref())?;
+        let format = TestAnalytics::parse(self.buffer.as_bytes(py), self.timestamp)?;
+        let tests = format.tests(desired_range, flags.as_deref())?;
         let mut collected_tests = vec![];
 
         for test in tests {
-            let test = test?;
-
-            collected_tests.push(TestAggregate {
-                name: test.name()?.into(),
-                test_id: "TODO".into(),
-                testsuite: Some(test.testsuite()?.into()),
-                flags: test.flags()?.into_iter().map(|s| s.into()).collect(),
-                failure_rate: test.aggregates().failure_rate,
-                flake_rate: test.aggregates().flake_rate,
-                updated_at: 0, // TODO
-                avg_duration: test.aggregates().avg_duration,
-                total_fail_count: test.aggregates().total_fail_count,
-                total_flaky_fail_count: test.aggregates().total_flaky_fail_count,
-                total_pass_count: test.aggregates().total_pass_count,
-                total_skip_count: test.aggregates().total_skip_count,
-                commits_where_fail: test.aggregates().failing_commits,
-                last_duration: 0., // TODO
-            });
+            collected_tests.push(TestAggregate::from_test(&test?)?);
         }
 
         Ok(collected_tests)
     }
+
+    #[pyo3(signature = (testsuite, name, interval_start, interval_end, flags=None))]
+    pub fn find_test_aggregates(
+        &self,
+        py: Python<'_>,
+        testsuite: &str,
+        name: &str,
+        interval_start: usize,
+        interval_end: usize,
+        flags: Option<Vec<String>>,
+    ) -> anyhow::Result<Vec<TestAggregate>> {
+        let flags: Option<Vec<_>> = flags
+            .as_ref()
+            .map(|flags| flags.iter().map(|flag| flag.as_str()).collect());
+        let desired_range = interval_start..interval_end;
+
+        let format = TestAnalytics::parse(self.buffer.as_bytes(py), self.timestamp)?;
+        let tests = format.find(testsuite, name, desired_range, flags.as_deref())?;
+        let mut collected_tests = vec![];
+
+        for test in tests {
+            collected_tests.push(TestAggregate::from_test(&test?)?);
+        }
+
+        Ok(collected_tests)
+    }
+}
+
+impl TestAggregate {
+    fn from_test(test: &Test) -> anyhow::Result<Self> {
+        Ok(TestAggregate {
+            name: test.name()?.into(),
+            test_id: "TODO".into(),
+            testsuite: Some(test.testsuite()?.into()),
+            classname: test.classname()?.into(),
+            computed_name: test.computed_name()?.into(),
+            flags: test.flags()?.into_iter().map(|s| s.into()).collect(),
+            failure_rate: test.aggregates().failure_rate,
+            flake_rate: test.aggregates().flake_rate,
+            updated_at: 0, // TODO
+            avg_duration: test.aggregates().avg_duration,
+            total_fail_count: test.aggregates().total_fail_count,
+            total_flaky_fail_count: test.aggregates().total_flaky_fail_count,
+            total_pass_count: test.aggregates().total_pass_count,
+            total_skip_count: test.aggregates().total_skip_count,
+            commits_where_fail: test.aggregates().failing_commits,
+            last_duration: 0., // TODO
+        })
+    }
 }
@@ -41,10 +41,6 @@ impl CommitHashesSet {
             .map_err(|_| TestAnalyticsErrorKind::InvalidCommitSetReference)?)
     }
 
-    pub fn read(&self, offset: u32) -> &[raw::CommitHash] {
-        Self::read_raw(self.inner.as_bytes(), offset).unwrap()
-    }
-
     /// Appends the `commit_hashes` to the existing set referenced by `existing_offset`.
     ///
     /// This returns a new offset in case any new commit hash was added, or returns
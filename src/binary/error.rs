@@ -36,6 +36,9 @@ pub enum TestAnalyticsErrorKind {
     /// The commit set reference was invalid
     #[error("could not resolve commit set reference")]
     InvalidCommitSetReference,
+    /// The historic data to import could not be parsed.
+    #[error("could not parse historic test data")]
+    InvalidHistoricData,
 }
 
 /// An error encountered during [`TestAnalytics`] creation or parsing.
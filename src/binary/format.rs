@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::collections::HashSet;
 use std::fmt;
 use std::ops::Range;
@@ -11,7 +12,13 @@ use watto::Pod;
 use super::*;
 
 /// The current format version.
-pub(crate) const TA_VERSION: u32 = 1;
+pub(crate) const TA_VERSION: u32 = 3;
+
+/// The first format version which includes the name index.
+const TA_VERSION_WITH_INDEX: u32 = 2;
+
+/// The first format version which includes the classname and computed name of tests.
+const TA_VERSION_WITH_CLASSNAME: u32 = 3;
 
 /// The serialized [`TestAnalytics`] binary format.
 ///
@@ -26,8 +33,15 @@ pub struct TestAnalytics<'data> {
     pub(crate) commithashes_bytes: &'data [u8],
     pub(crate) string_bytes: &'data [u8],
 
-    pub(crate) tests: &'data [raw::Test],
+    /// The test records, which are migrated to the current layout for older format versions.
+    pub(crate) tests: Cow<'data, [raw::Test]>,
     pub(crate) testdata: &'data [raw::TestData],
+
+    /// Indices into `tests`, sorted by testsuite and name, followed by classname
+    /// and computed name starting with format version 3.
+    ///
+    /// This is only available starting with format version 2.
+    pub(crate) index: Option<&'data [u32]>,
 }
 
 impl<'data> TestAnalytics<'data> {
@@ -40,12 +54,20 @@ impl<'data> TestAnalytics<'data> {
             return Err(TestAnalyticsErrorKind::InvalidMagic(header.magic).into());
         }
 
-        if header.version != TA_VERSION {
+        if !(1..=TA_VERSION).contains(&header.version) {
             return Err(TestAnalyticsErrorKind::WrongVersion(header.version).into());
         }
 
-        let (tests, rest) = raw::Test::slice_from_prefix(rest, header.num_tests as usize)
-            .ok_or(TestAnalyticsErrorKind::InvalidTables)?;
+        let (tests, rest) = if header.version >= TA_VERSION_WITH_CLASSNAME {
+            let (tests, rest) = raw::Test::slice_from_prefix(rest, header.num_tests as usize)
+                .ok_or(TestAnalyticsErrorKind::InvalidTables)?;
+            (Cow::Borrowed(tests), rest)
+        } else {
+            let (tests, rest) = raw::LegacyTest::slice_from_prefix(rest, header.num_tests as usize)
+                .ok_or(TestAnalyticsErrorKind::InvalidTables)?;
+            let tests = tests.iter().map(|test| raw::Test::from(*test)).collect();
+            (Cow::Owned(tests), rest)
+        };
 
         let expected_data = header.num_tests as usize * header.num_days as usize;
 
@@ -55,6 +77,17 @@ impl<'data> TestAnalytics<'data> {
         let (flags_set, rest) = u32::slice_from_prefix(rest, header.flags_set_len as usize)
             .ok_or(TestAnalyticsErrorKind::InvalidTables)?;
 
+        let (index, rest) = if header.version >= TA_VERSION_WITH_INDEX {
+            let (index, rest) = u32::slice_from_prefix(rest, header.num_tests as usize)
+                .ok_or(TestAnalyticsErrorKind::InvalidTables)?;
+            if index.iter().any(|idx| *idx >= header.num_tests) {
+                return Err(TestAnalyticsErrorKind::InvalidTables.into());
+            }
+            (Some(index), rest)
+        } else {
+            (None, rest)
+        };
+
         let (commithashes_bytes, rest) =
             u8::slice_from_prefix(rest, header.commithashes_bytes as usize)
                 .ok_or(TestAnalyticsErrorKind::InvalidTables)?;
@@ -79,6 +112,8 @@ impl<'data> TestAnalytics<'data> {
 
             tests,
             testdata,
+
+            index,
         })
     }
 
@@ -91,25 +126,9 @@ impl<'data> TestAnalytics<'data> {
         impl Iterator<Item = Result<Test<'data, '_>, TestAnalyticsError>> + '_,
         TestAnalyticsError,
     > {
-        let matching_flags_sets = if let Some(flags) = flags {
-            let flag_sets = self.flags_set.iter(self.string_bytes);
-
-            let mut matching_flags_sets: SmallVec<u32, 4> = Default::default();
-            for res in flag_sets {
-                let (offset, flag_set) = res?;
-                if flags.iter().any(|flag| flag_set.contains(flag)) {
-                    matching_flags_sets.push(offset);
-                }
-            }
-            matching_flags_sets.sort();
-
-            Some(matching_flags_sets)
-        } else {
-            None
-        };
+        let matching_flags_sets = self.matching_flags_sets(flags)?;
         let mut failing_commits = HashSet::new();
 
-        let num_days = self.header.num_days as usize;
         let tests = self.tests.iter().enumerate().filter_map(move |(i, test)| {
             if let Some(flags_sets) = &matching_flags_sets {
                 if !flags_sets.contains(&test.flag_set_offset) {
@@ -117,32 +136,154 @@ impl<'data> TestAnalytics<'data> {
                 }
             }
 
-            let start_idx = i * num_days;
-            let latest_test_timestamp = self.testdata[start_idx].last_timestamp;
+            self.test_at(i, desired_range.clone(), &mut failing_commits)
+        });
+        Ok(tests)
+    }
 
-            let today_offset = offset_from_today(latest_test_timestamp, self.timestamp);
-            let data_range = start_idx..start_idx + test.valid_data as usize;
-            let adjusted_range =
-                adjust_selection_range(data_range, desired_range.clone(), today_offset);
+    /// Finds the [`Test`]s with the given `testsuite` and `name`, one for each classname,
+    /// computed name and set of flags.
+    ///
+    /// This does a binary search using the name index, or falls back to scanning
+    /// all the tests for files which do not have an index.
+    pub fn find(
+        &self,
+        testsuite: &str,
+        name: &str,
+        desired_range: Range<usize>,
+        flags: Option<&[&str]>,
+    ) -> Result<
+        impl Iterator<Item = Result<Test<'data, '_>, TestAnalyticsError>> + '_,
+        TestAnalyticsError,
+    > {
+        let matching_flags_sets = self.matching_flags_sets(flags)?;
+
+        let needle = (testsuite, name);
+        let mut found: SmallVec<usize, 4> = SmallVec::new();
+        match self.index {
+            Some(index) => {
+                let start =
+                    try_partition_point(index, |idx| Ok(self.test_names(*idx as usize)? < needle))?;
+                let len = try_partition_point(&index[start..], |idx| {
+                    Ok(self.test_names(*idx as usize)? == needle)
+                })?;
+                found.extend(index[start..start + len].iter().map(|idx| *idx as usize));
+            }
+            None => {
+                for i in 0..self.tests.len() {
+                    if self.test_names(i)? == needle {
+                        found.push(i);
+                    }
+                }
+            }
+        }
 
-            if adjusted_range.is_empty() {
-                return None;
+        let mut failing_commits = HashSet::new();
+        let tests = found.into_iter().filter_map(move |i| {
+            if let Some(flags_sets) = &matching_flags_sets {
+                if !flags_sets.contains(&self.tests[i].flag_set_offset) {
+                    return None;
+                }
             }
 
-            let aggregates = Aggregates::from_data(
-                self.commithashes_bytes,
-                &mut failing_commits,
-                &self.testdata[adjusted_range],
-            );
-
-            Some(aggregates.map(|aggregates| Test {
-                container: self,
-                data: test,
-                aggregates,
-            }))
+            self.test_at(i, desired_range.clone(), &mut failing_commits)
         });
         Ok(tests)
     }
+
+    /// Returns the sorted offsets of all the flag sets which contain any of the given `flags`.
+    fn matching_flags_sets(
+        &self,
+        flags: Option<&[&str]>,
+    ) -> Result<Option<SmallVec<u32, 4>>, TestAnalyticsError> {
+        let Some(flags) = flags else {
+            return Ok(None);
+        };
+        let flag_sets = self.flags_set.iter(self.string_bytes);
+
+        let mut matching_flags_sets: SmallVec<u32, 4> = Default::default();
+        for res in flag_sets {
+            let (offset, flag_set) = res?;
+            if flags.iter().any(|flag| flag_set.contains(flag)) {
+                matching_flags_sets.push(offset);
+            }
+        }
+        matching_flags_sets.sort();
+
+        Ok(Some(matching_flags_sets))
+    }
+
+    /// Returns the testsuite and name of the test at index `i`.
+    fn test_names(&self, i: usize) -> Result<(&'data str, &'data str), TestAnalyticsError> {
+        let test = &self.tests[i];
+        let testsuite = read_string(self.string_bytes, test.testsuite_offset)?;
+        let name = read_string(self.string_bytes, test.name_offset)?;
+        Ok((testsuite, name))
+    }
+
+    /// Creates the [`Test`] at index `i`, with its data aggregated over `desired_range`.
+    ///
+    /// Returns `None` if the test has no data within that range.
+    fn test_at(
+        &self,
+        i: usize,
+        desired_range: Range<usize>,
+        failing_commits: &mut HashSet<CommitHash>,
+    ) -> Option<Result<Test<'data, '_>, TestAnalyticsError>> {
+        let test = &self.tests[i];
+        let num_days = self.header.num_days as usize;
+
+        let start_idx = i * num_days;
+        let latest_test_timestamp = self.testdata[start_idx].last_timestamp;
+
+        let today_offset = offset_from_today(latest_test_timestamp, self.timestamp);
+        let data_range = start_idx..start_idx + test.valid_data as usize;
+        let adjusted_range = adjust_selection_range(data_range, desired_range, today_offset);
+
+        if adjusted_range.is_empty() {
+            return None;
+        }
+
+        let aggregates = Aggregates::from_data(
+            self.commithashes_bytes,
+            failing_commits,
+            &self.testdata[adjusted_range],
+        );
+
+        Some(aggregates.map(|aggregates| Test {
+            container: self,
+            data: test,
+            aggregates,
+        }))
+    }
+}
+
+/// Reads the string at `offset` from the string table.
+///
+/// The [`raw::NO_STRING`] offset of migrated test records reads as an empty string.
+pub(crate) fn read_string(string_bytes: &[u8], offset: u32) -> Result<&str, TestAnalyticsError> {
+    if offset == raw::NO_STRING {
+        return Ok("");
+    }
+    watto::StringTable::read(string_bytes, offset as usize)
+        .map_err(|_| TestAnalyticsErrorKind::InvalidStringReference.into())
+}
+
+/// Like [`slice::partition_point`], but with a fallible `pred`.
+fn try_partition_point<T>(
+    slice: &[T],
+    mut pred: impl FnMut(&T) -> Result<bool, TestAnalyticsError>,
+) -> Result<usize, TestAnalyticsError> {
+    let (mut low, mut high) = (0, slice.len());
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if pred(&slice[mid])? {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    Ok(low)
 }
 
 impl fmt::Debug for TestAnalytics<'_> {
@@ -160,7 +301,7 @@ impl fmt::Debug for TestAnalytics<'_> {
 #[derive(Debug, Clone)]
 pub struct Test<'data, 'parsed> {
     container: &'parsed TestAnalytics<'data>,
-    data: &'data raw::Test,
+    data: &'parsed raw::Test,
 
     aggregates: Aggregates,
 }
@@ -168,17 +309,26 @@ pub struct Test<'data, 'parsed> {
 impl<'data> Test<'data, '_> {
     /// Returns the testsuite of the test.
     pub fn testsuite(&self) -> Result<&'data str, TestAnalyticsError> {
-        watto::StringTable::read(
-            self.container.string_bytes,
-            self.data.testsuite_offset as usize,
-        )
-        .map_err(|_| TestAnalyticsErrorKind::InvalidStringReference.into())
+        read_string(self.container.string_bytes, self.data.testsuite_offset)
     }
 
     /// Returns the name of the test.
     pub fn name(&self) -> Result<&'data str, TestAnalyticsError> {
-        watto::StringTable::read(self.container.string_bytes, self.data.name_offset as usize)
-            .map_err(|_| TestAnalyticsErrorKind::InvalidStringReference.into())
+        read_string(self.container.string_bytes, self.data.name_offset)
+    }
+
+    /// Returns the classname of the test.
+    ///
+    /// This is empty for tests which were recorded before format version 3.
+    pub fn classname(&self) -> Result<&'data str, TestAnalyticsError> {
+        read_string(self.container.string_bytes, self.data.classname_offset)
+    }
+
+    /// Returns the computed name of the test.
+    ///
+    /// This is empty for tests which were recorded before format version 3.
+    pub fn computed_name(&self) -> Result<&'data str, TestAnalyticsError> {
+        read_string(self.container.string_bytes, self.data.computed_name_offset)
     }
 
     pub fn flags(&self) -> Result<SmallVec<&str, 4>, TestAnalyticsError> {
@@ -0,0 +1,213 @@
+use serde::de::{self, Deserializer};
+use serde::Deserialize;
+
+use super::*;
+
+/// Pre-aggregated test data for a single test on a single day.
+///
+/// This is the input to [`TestAnalyticsWriter::import_historic`], and can be
+/// parsed from JSON via [`HistoricTestData::from_json`] or from CSV via
+/// [`HistoricTestData::from_csv`].
+///
+/// In CSV, the `flags` and `failing_commits` columns hold whitespace-separated lists.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct HistoricTestData {
+    /// A timestamp within the day this data was recorded.
+    pub timestamp: u32,
+    #[serde(default)]
+    pub testsuite: String,
+    pub name: String,
+    #[serde(default)]
+    pub classname: String,
+    #[serde(default)]
+    pub computed_name: String,
+    #[serde(default, deserialize_with = "deserialize_list")]
+    pub flags: Vec<String>,
+
+    #[serde(default)]
+    pub total_pass_count: u16,
+    #[serde(default)]
+    pub total_fail_count: u16,
+    #[serde(default)]
+    pub total_skip_count: u16,
+    #[serde(default)]
+    pub total_flaky_fail_count: u16,
+    #[serde(default)]
+    pub total_duration: f32,
+    #[serde(default)]
+    pub last_duration: f32,
+
+    /// The hex-encoded commit hashes on which the test failed on that day.
+    #[serde(default, deserialize_with = "deserialize_commit_hashes")]
+    pub failing_commits: Vec<CommitHash>,
+}
+
+impl HistoricTestData {
+    /// Parses a JSON array of historic test data.
+    pub fn from_json(buf: &[u8]) -> Result<Vec<Self>, TestAnalyticsError> {
+        serde_json::from_slice(buf).map_err(invalid_historic_data)
+    }
+
+    /// Parses CSV with a header row into historic test data.
+    pub fn from_csv(buf: &[u8]) -> Result<Vec<Self>, TestAnalyticsError> {
+        let mut reader = csv::Reader::from_reader(buf);
+        let headers = reader.headers().map_err(invalid_historic_data)?.clone();
+
+        // the csv deserializer infers the type of the cells it hands to untagged enums,
+        // which would turn a flag like `3.11` into a float, so the lists are split here
+        let is_list = |header: &str| matches!(header, "flags" | "failing_commits");
+        let scalar_headers: csv::StringRecord =
+            headers.iter().filter(|header| !is_list(header)).collect();
+
+        reader
+            .into_records()
+            .map(|record| {
+                let record = record.map_err(invalid_historic_data)?;
+                let scalars: csv::StringRecord = record
+                    .iter()
+                    .zip(&headers)
+                    .filter(|(_, header)| !is_list(header))
+                    .map(|(cell, _)| cell)
+                    .collect();
+                let mut data: Self = scalars
+                    .deserialize(Some(&scalar_headers))
+                    .map_err(invalid_historic_data)?;
+
+                for (cell, header) in record.iter().zip(&headers) {
+                    let list = cell.split_whitespace().map(String::from).collect();
+                    match header {
+                        "flags" => data.flags = list,
+                        "failing_commits" => {
+                            data.failing_commits =
+                                parse_commit_hashes(&list).map_err(invalid_historic_data)?
+                        }
+                        _ => {}
+                    }
+                }
+                Ok(data)
+            })
+            .collect()
+    }
+}
+
+fn invalid_historic_data(
+    err: impl Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
+) -> TestAnalyticsError {
+    TestAnalyticsError {
+        kind: TestAnalyticsErrorKind::InvalidHistoricData,
+        source: Some(err.into()),
+    }
+}
+
+/// A list given either as an array, or as a whitespace-separated string.
+///
+/// This is only used for JSON, see [`HistoricTestData::from_csv`].
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StringOrList {
+    String(String),
+    List(Vec<String>),
+}
+
+impl StringOrList {
+    fn into_list(self) -> Vec<String> {
+        match self {
+            StringOrList::String(s) => s.split_whitespace().map(String::from).collect(),
+            StringOrList::List(list) => list,
+        }
+    }
+}
+
+fn deserialize_list<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    Ok(StringOrList::deserialize(deserializer)?.into_list())
+}
+
+fn deserialize_commit_hashes<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<CommitHash>, D::Error> {
+    let hashes = StringOrList::deserialize(deserializer)?.into_list();
+    parse_commit_hashes(&hashes).map_err(de::Error::custom)
+}
+
+fn parse_commit_hashes(hashes: &[String]) -> Result<Vec<CommitHash>, String> {
+    hashes
+        .iter()
+        .map(|hash| {
+            let hash = hash.get(..40).unwrap_or(hash);
+            let mut commit_hash = CommitHash::default();
+            base16ct::mixed::decode(hash, &mut commit_hash.0)
+                .map_err(|_| format!("invalid commit hash: {hash}"))?;
+            Ok(commit_hash)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_json() {
+        let json = br#"[
+            {"timestamp": 0, "name": "abc", "flags": ["flag-a"], "total_pass_count": 2,
+             "failing_commits": ["0101010101010101010101010101010101010101"]},
+            {"timestamp": 0, "testsuite": "suite", "name": "def", "flags": "flag-a flag-b"}
+        ]"#;
+        let parsed = HistoricTestData::from_json(json).unwrap();
+
+        assert_eq!(parsed[0].name, "abc");
+        assert_eq!(parsed[0].flags, &["flag-a"]);
+        assert_eq!(parsed[0].total_pass_count, 2);
+        assert_eq!(parsed[0].failing_commits, &[CommitHash([1; 20])]);
+
+        assert_eq!(parsed[1].testsuite, "suite");
+        assert_eq!(parsed[1].flags, &["flag-a", "flag-b"]);
+        assert!(parsed[1].failing_commits.is_empty());
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        let csv = b"timestamp,testsuite,name,flags,total_pass_count,total_fail_count,total_skip_count,total_flaky_fail_count,total_duration,last_duration,failing_commits
+86400,suite,abc,flag-a flag-b,1,2,0,0,3.0,1.0,0101010101010101010101010101010101010101 0202020202020202020202020202020202020202
+";
+        let parsed = HistoricTestData::from_csv(csv).unwrap();
+
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].timestamp, 86400);
+        assert_eq!(parsed[0].flags, &["flag-a", "flag-b"]);
+        assert_eq!(parsed[0].total_fail_count, 2);
+        assert_eq!(
+            parsed[0].failing_commits,
+            &[CommitHash([1; 20]), CommitHash([2; 20])]
+        );
+    }
+
+    #[test]
+    fn test_parse_csv_numeric_lists() {
+        let csv = b"timestamp,name,flags,failing_commits
+0,abc,3.11,1111111111111111111111111111111111111111
+0,def,true 1,
+";
+        let parsed = HistoricTestData::from_csv(csv).unwrap();
+
+        assert_eq!(parsed[0].flags, &["3.11"]);
+        assert_eq!(parsed[0].failing_commits, &[CommitHash([0x11; 20])]);
+        assert_eq!(parsed[1].flags, &["true", "1"]);
+        assert!(parsed[1].failing_commits.is_empty());
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        let err = HistoricTestData::from_json(b"{}").unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            TestAnalyticsErrorKind::InvalidHistoricData
+        ));
+
+        let json = br#"[{"timestamp": 0, "name": "abc", "failing_commits": ["xyz"]}]"#;
+        assert!(HistoricTestData::from_json(json).is_err());
+
+        let csv = b"timestamp,name,failing_commits\n0,abc,xyz\n";
+        assert!(HistoricTestData::from_csv(csv).is_err());
+    }
+}
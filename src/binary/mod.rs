@@ -3,6 +3,7 @@ mod commithashes_set;
 mod error;
 mod flags_set;
 mod format;
+mod import;
 mod raw;
 mod timestamps;
 mod writer;
@@ -10,33 +11,27 @@ mod writer;
 pub use bindings::{AggregationReader, BinaryFormatWriter, TestAggregate};
 pub use error::{TestAnalyticsError, TestAnalyticsErrorKind};
 pub use format::{Test, TestAnalytics};
+pub use import::HistoricTestData;
 pub use raw::CommitHash;
-pub use writer::TestAnalyticsWriter;
+pub use writer::{Aggregation, TestAnalyticsWriter};
 
 #[cfg(test)]
 mod tests {
+    use std::mem;
+
     use raw::CommitHash;
     use timestamps::DAY;
+    use watto::Pod;
 
-    use crate::{
-        testrun::{Outcome, PropertiesValue, Testrun},
-        validated_string::ValidatedString,
-    };
+    use crate::testrun::{Framework, Outcome, Testrun};
 
     use super::*;
 
     fn test() -> Testrun {
         Testrun {
             name: "abc".try_into().unwrap(),
-            classname: ValidatedString::default(),
             duration: Some(1.0),
-            outcome: Outcome::Pass,
-            testsuite: ValidatedString::default(),
-            failure_message: None,
-            filename: None,
-            build_url: None,
-            computed_name: ValidatedString::default(),
-            properties: PropertiesValue(None),
+            ..Default::default()
         }
     }
 
@@ -90,6 +85,31 @@ mod tests {
         assert!(tests.next().is_none());
     }
 
+    #[test]
+    fn test_flaky() {
+        let mut test = test();
+
+        let mut writer = TestAnalyticsWriter::new(2);
+        let mut session = writer.start_session(0, CommitHash::default(), &[]);
+
+        session.insert(&test);
+        test.flaky = true;
+        session.insert(&test);
+
+        let mut buf = vec![];
+        writer.serialize(&mut buf).unwrap();
+
+        let parsed = TestAnalytics::parse(&buf, 0).unwrap();
+        let mut tests = parsed.tests(0..60, None).unwrap();
+
+        let abc = tests.next().unwrap().unwrap();
+        let aggregates = abc.aggregates();
+        assert_eq!(aggregates.total_pass_count, 2);
+        assert_eq!(aggregates.total_fail_count, 0);
+        assert_eq!(aggregates.total_flaky_fail_count, 1);
+        assert_eq!(aggregates.flake_rate, 0.5);
+    }
+
     #[test]
     fn test_testsuites() {
         let mut test = test();
@@ -254,6 +274,56 @@ mod tests {
         assert!(tests.next().is_none());
     }
 
+    #[test]
+    fn test_merge_all() {
+        let test = test();
+
+        let mut bufs = vec![];
+        for (timestamp, flags) in [(0, ["flag-a"]), (DAY, ["flag-b"]), (2 * DAY, ["flag-a"])] {
+            let mut writer = TestAnalyticsWriter::new(2 + timestamp as usize / DAY as usize);
+            let mut session = writer.start_session(timestamp, CommitHash::default(), &flags);
+            session.insert(&test);
+            let mut buf = vec![];
+            writer.serialize(&mut buf).unwrap();
+            bufs.push(buf);
+        }
+
+        let parsed: Vec<_> = bufs
+            .iter()
+            .map(|buf| TestAnalytics::parse(buf, 2 * DAY).unwrap())
+            .collect();
+
+        let merged = TestAnalyticsWriter::merge_all(&parsed).unwrap();
+        let mut buf = vec![];
+        merged.serialize(&mut buf).unwrap();
+
+        let parsed = TestAnalytics::parse(&buf, 2 * DAY).unwrap();
+        assert_eq!(parsed.header.num_days, 4);
+
+        let mut tests = parsed.tests(0..60, None).unwrap();
+
+        let abc = tests.next().unwrap().unwrap();
+        assert_eq!(abc.flags().unwrap(), &["flag-a"]);
+        assert_eq!(abc.aggregates().total_pass_count, 2);
+
+        let abc = tests.next().unwrap().unwrap();
+        assert_eq!(abc.flags().unwrap(), &["flag-b"]);
+        assert_eq!(abc.aggregates().total_pass_count, 1);
+
+        assert!(tests.next().is_none());
+
+        // the data from the first and last inputs ends up in the right buckets
+        let mut tests = parsed.tests(2..3, Some(&["flag-a"])).unwrap();
+        let abc = tests.next().unwrap().unwrap();
+        assert_eq!(abc.aggregates().total_pass_count, 1);
+        assert!(tests.next().is_none());
+
+        let mut tests = parsed.tests(0..1, Some(&["flag-a"])).unwrap();
+        let abc = tests.next().unwrap().unwrap();
+        assert_eq!(abc.aggregates().total_pass_count, 1);
+        assert!(tests.next().is_none());
+    }
+
     #[test]
     fn test_garbage_collection() {
         let test = test();
@@ -305,6 +375,408 @@ mod tests {
         assert!(tests.next().is_none());
     }
 
+    #[test]
+    fn test_remove_and_rename_tests() {
+        let mut test = test();
+        test.outcome = Outcome::Failure;
+
+        let mut writer = TestAnalyticsWriter::new(7);
+        let mut session = writer.start_session(0, CommitHash([0; 20]), &[]);
+        session.insert(&test);
+        test.name = "def".try_into().unwrap();
+        session.insert(&test);
+        test.name = "ghi".try_into().unwrap();
+        session.insert(&test);
+        // the renamed test has more recent data, in a different bucket
+        let mut session = writer.start_session(DAY, CommitHash([1; 20]), &[]);
+        test.name = "abc".try_into().unwrap();
+        session.insert(&test);
+
+        writer
+            .remove_tests(|_testsuite, name, _flags| name == "ghi")
+            .unwrap();
+        writer.rename_test("", "def", "", "abc", "", "").unwrap();
+
+        let mut buf = vec![];
+        writer.serialize(&mut buf).unwrap();
+
+        let parsed = TestAnalytics::parse(&buf, DAY).unwrap();
+        let mut tests = parsed.tests(0..60, None).unwrap();
+
+        let abc = tests.next().unwrap().unwrap();
+        assert_eq!(abc.name().unwrap(), "abc");
+        assert_eq!(abc.aggregates().total_fail_count, 3);
+        assert_eq!(abc.aggregates().failing_commits, 2);
+        assert!(tests.next().is_none());
+
+        let mut tests = parsed.tests(1..2, None).unwrap();
+        let abc = tests.next().unwrap().unwrap();
+        assert_eq!(abc.aggregates().total_fail_count, 2);
+        assert!(tests.next().is_none());
+    }
+
+    #[test]
+    fn test_rename_test_onto_existing_test() {
+        let mut test = test();
+        test.classname = "tests.OldTest".try_into().unwrap();
+        test.name = "test_old".try_into().unwrap();
+        test.computed_name = "tests.OldTest.test_old".try_into().unwrap();
+        test.outcome = Outcome::Failure;
+
+        let mut writer = TestAnalyticsWriter::new(7);
+        let mut session = writer.start_session(0, CommitHash([0; 20]), &[]);
+        session.insert(&test);
+
+        let mut renamed = test.clone();
+        renamed.classname = "tests.NewTest".try_into().unwrap();
+        renamed.name = "test_new".try_into().unwrap();
+        renamed.computed_name = "tests.NewTest.test_new".try_into().unwrap();
+        renamed.outcome = Outcome::Pass;
+        session.insert(&renamed);
+
+        writer
+            .rename_test(
+                "",
+                "test_old",
+                "",
+                "test_new",
+                "tests.NewTest",
+                "tests.NewTest.test_new",
+            )
+            .unwrap();
+        // later runs of the new name are merged as well
+        let mut session = writer.start_session(0, CommitHash([1; 20]), &[]);
+        session.insert(&renamed);
+
+        let mut buf = vec![];
+        writer.serialize(&mut buf).unwrap();
+
+        let parsed = TestAnalytics::parse(&buf, 0).unwrap();
+        let mut tests = parsed.tests(0..60, None).unwrap();
+
+        let renamed = tests.next().unwrap().unwrap();
+        assert_eq!(renamed.name().unwrap(), "test_new");
+        assert_eq!(renamed.classname().unwrap(), "tests.NewTest");
+        assert_eq!(renamed.computed_name().unwrap(), "tests.NewTest.test_new");
+        assert_eq!(renamed.aggregates().total_fail_count, 1);
+        assert_eq!(renamed.aggregates().total_pass_count, 2);
+        assert!(tests.next().is_none());
+    }
+
+    #[test]
+    fn test_remove_and_rename_flags() {
+        let test = test();
+
+        let mut writer = TestAnalyticsWriter::new(2);
+        let mut session = writer.start_session(0, CommitHash::default(), &["flag-a"]);
+        session.insert(&test);
+        let mut session = writer.start_session(0, CommitHash::default(), &["flag-a", "flag-b"]);
+        session.insert(&test);
+        let mut session = writer.start_session(0, CommitHash::default(), &["flag-c"]);
+        session.insert(&test);
+
+        writer.remove_flag("flag-b").unwrap();
+        writer.rename_flag("flag-c", "flag-d").unwrap();
+
+        let mut buf = vec![];
+        writer.serialize(&mut buf).unwrap();
+
+        let parsed = TestAnalytics::parse(&buf, 0).unwrap();
+        let mut tests = parsed.tests(0..60, None).unwrap();
+
+        // the tests which had `flag-a` and `flag-a, flag-b` are merged
+        let abc = tests.next().unwrap().unwrap();
+        assert_eq!(abc.flags().unwrap(), &["flag-a"]);
+        assert_eq!(abc.aggregates().total_pass_count, 2);
+
+        let abc = tests.next().unwrap().unwrap();
+        assert_eq!(abc.flags().unwrap(), &["flag-d"]);
+        assert_eq!(abc.aggregates().total_pass_count, 1);
+
+        assert!(tests.next().is_none());
+
+        let mut tests = parsed.tests(0..60, Some(&["flag-b", "flag-c"])).unwrap();
+        assert!(tests.next().is_none());
+    }
+
+    #[test]
+    fn test_find() {
+        let mut test = test();
+
+        let mut writer = TestAnalyticsWriter::new(2);
+        for flag in ["flag-b", "flag-a"] {
+            let mut session = writer.start_session(0, CommitHash::default(), &[flag]);
+            for name in ["xyz", "abc", "def"] {
+                for testsuite in ["suite-2", "suite-1"] {
+                    test.testsuite = testsuite.try_into().unwrap();
+                    test.name = name.try_into().unwrap();
+                    session.insert(&test);
+                }
+            }
+        }
+
+        let mut buf = vec![];
+        writer.serialize(&mut buf).unwrap();
+
+        let parsed = TestAnalytics::parse(&buf, 0).unwrap();
+        assert!(parsed.index.is_some());
+
+        let assert_found = |parsed: &TestAnalytics| {
+            let mut tests = parsed.find("suite-1", "def", 0..60, None).unwrap();
+            let def = tests.next().unwrap().unwrap();
+            assert_eq!(def.testsuite().unwrap(), "suite-1");
+            assert_eq!(def.name().unwrap(), "def");
+            let def = tests.next().unwrap().unwrap();
+            assert_eq!(def.testsuite().unwrap(), "suite-1");
+            assert_eq!(def.name().unwrap(), "def");
+            assert!(tests.next().is_none());
+
+            let mut tests = parsed
+                .find("suite-2", "xyz", 0..60, Some(&["flag-a"]))
+                .unwrap();
+            let xyz = tests.next().unwrap().unwrap();
+            assert_eq!(xyz.name().unwrap(), "xyz");
+            assert_eq!(xyz.flags().unwrap(), &["flag-a"]);
+            assert!(tests.next().is_none());
+
+            let mut tests = parsed.find("suite-3", "abc", 0..60, None).unwrap();
+            assert!(tests.next().is_none());
+            let mut tests = parsed.find("suite-1", "ghi", 0..60, None).unwrap();
+            assert!(tests.next().is_none());
+        };
+        assert_found(&parsed);
+
+        // files written with format version 2 have test records without a classname
+        let buf_v2 = legacy_buf(&buf, &parsed);
+        let parsed_v2 = TestAnalytics::parse(&buf_v2, 0).unwrap();
+        assert!(parsed_v2.index.is_some());
+        assert_found(&parsed_v2);
+
+        // files written with format version 1 do not have an index
+        let index_start = mem::size_of::<raw::Header>()
+            + mem::size_of::<raw::LegacyTest>() * parsed.tests.len()
+            + mem::size_of_val(parsed.testdata)
+            + mem::size_of_val(&*parsed.flags_set.table);
+        let index_end = index_start + mem::size_of_val(parsed.index.unwrap());
+        let mut buf_v1 = buf_v2.clone();
+        buf_v1.drain(index_start..index_end);
+        buf_v1[4..8].copy_from_slice(&1u32.to_le_bytes());
+
+        let parsed = TestAnalytics::parse(&buf_v1, 0).unwrap();
+        assert!(parsed.index.is_none());
+        assert_found(&parsed);
+    }
+
+    /// Rewrites the test records of `buf` into the layout of format version 2.
+    fn legacy_buf(buf: &[u8], parsed: &TestAnalytics) -> Vec<u8> {
+        let tests_start = mem::size_of::<raw::Header>();
+        let tests_end = tests_start + mem::size_of_val(&*parsed.tests);
+        let legacy_tests: Vec<_> = parsed
+            .tests
+            .iter()
+            .map(|test| raw::LegacyTest {
+                testsuite_offset: test.testsuite_offset,
+                name_offset: test.name_offset,
+                flag_set_offset: test.flag_set_offset,
+                valid_data: test.valid_data,
+            })
+            .collect();
+
+        let mut legacy_buf = buf[..tests_start].to_vec();
+        legacy_buf.extend_from_slice(legacy_tests.as_slice().as_bytes());
+        legacy_buf.extend_from_slice(&buf[tests_end..]);
+        legacy_buf[4..8].copy_from_slice(&2u32.to_le_bytes());
+        legacy_buf
+    }
+
+    #[test]
+    fn test_classname() {
+        let mut test = test();
+        test.name = "test_create".try_into().unwrap();
+
+        let mut writer = TestAnalyticsWriter::new(2);
+        let mut session = writer.start_session(0, CommitHash::default(), &[]);
+        for classname in ["tests.test_users.TestUsers", "tests.test_teams.TestTeams"] {
+            test.classname = classname.try_into().unwrap();
+            test.computed_name = format!("{classname}::test_create").try_into().unwrap();
+            session.insert(&test);
+        }
+        session.insert(&test);
+
+        let mut buf = vec![];
+        writer.serialize(&mut buf).unwrap();
+
+        let parsed = TestAnalytics::parse(&buf, 0).unwrap();
+        let mut tests = parsed.find("", "test_create", 0..60, None).unwrap();
+        let teams = tests.next().unwrap().unwrap();
+        assert_eq!(teams.classname().unwrap(), "tests.test_teams.TestTeams");
+        assert_eq!(
+            teams.computed_name().unwrap(),
+            "tests.test_teams.TestTeams::test_create"
+        );
+        assert_eq!(teams.aggregates().total_pass_count, 2);
+        let users = tests.next().unwrap().unwrap();
+        assert_eq!(users.classname().unwrap(), "tests.test_users.TestUsers");
+        assert_eq!(users.aggregates().total_pass_count, 1);
+        assert!(tests.next().is_none());
+    }
+
+    #[test]
+    fn test_parameters() {
+        let insert_all = |aggregation| {
+            let mut test = test();
+            test.framework = Some(Framework::Pytest);
+            test.base_computed_name = Some("test_a.py::test_add".try_into().unwrap());
+
+            let mut writer = TestAnalyticsWriter::new(2);
+            writer.set_aggregation(aggregation);
+            let mut session = writer.start_session(0, CommitHash::default(), &[]);
+            for (parameters, outcome) in [("1-2", Outcome::Pass), ("2-3", Outcome::Failure)] {
+                test.name = format!("test_add[{parameters}]").try_into().unwrap();
+                test.computed_name = format!("test_a.py::test_add[{parameters}]")
+                    .try_into()
+                    .unwrap();
+                test.parameters = Some(parameters.into());
+                test.outcome = outcome;
+                session.insert(&test);
+            }
+
+            let mut buf = vec![];
+            writer.serialize(&mut buf).unwrap();
+            buf
+        };
+
+        let buf = insert_all(Aggregation::PerParameterSet);
+        let parsed = TestAnalytics::parse(&buf, 0).unwrap();
+        let tests: Vec<_> = parsed
+            .tests(0..60, None)
+            .unwrap()
+            .map(|test| test.unwrap().computed_name().unwrap().to_string())
+            .collect();
+        assert_eq!(
+            tests,
+            ["test_a.py::test_add[1-2]", "test_a.py::test_add[2-3]"]
+        );
+
+        let buf = insert_all(Aggregation::PerBaseTest);
+        let parsed = TestAnalytics::parse(&buf, 0).unwrap();
+        let mut tests = parsed.tests(0..60, None).unwrap();
+        let test = tests.next().unwrap().unwrap();
+        assert_eq!(test.name().unwrap(), "test_add");
+        assert_eq!(test.computed_name().unwrap(), "test_a.py::test_add");
+        assert_eq!(test.aggregates().total_pass_count, 1);
+        assert_eq!(test.aggregates().total_fail_count, 1);
+        assert!(tests.next().is_none());
+    }
+
+    #[test]
+    fn test_migrate_legacy_format() {
+        let test = test();
+
+        let mut writer = TestAnalyticsWriter::new(2);
+        let mut session = writer.start_session(0, CommitHash::default(), &[]);
+        session.insert(&test);
+
+        let mut buf = vec![];
+        writer.serialize(&mut buf).unwrap();
+        let parsed = TestAnalytics::parse(&buf, 0).unwrap();
+        let buf_v2 = legacy_buf(&buf, &parsed);
+
+        let parsed = TestAnalytics::parse(&buf_v2, 0).unwrap();
+        let mut tests = parsed.tests(0..60, None).unwrap();
+        let abc = tests.next().unwrap().unwrap();
+        assert_eq!(abc.name().unwrap(), "abc");
+        assert_eq!(abc.classname().unwrap(), "");
+        assert_eq!(abc.computed_name().unwrap(), "");
+
+        // migrated records are merged with new ones without a classname
+        let mut writer = TestAnalyticsWriter::from_existing_format(&parsed).unwrap();
+        let mut session = writer.start_session(0, CommitHash::default(), &[]);
+        session.insert(&test);
+
+        let mut buf = vec![];
+        writer.serialize(&mut buf).unwrap();
+
+        let parsed = TestAnalytics::parse(&buf, 0).unwrap();
+        assert_eq!(parsed.header.version, format::TA_VERSION);
+        let mut tests = parsed.tests(0..60, None).unwrap();
+        let abc = tests.next().unwrap().unwrap();
+        assert_eq!(abc.classname().unwrap(), "");
+        assert_eq!(abc.aggregates().total_pass_count, 2);
+        assert!(tests.next().is_none());
+
+        // as well as when merging files of different versions
+        let parsed_v2 = TestAnalytics::parse(&buf_v2, 0).unwrap();
+        let writer = TestAnalyticsWriter::merge(&parsed, &parsed_v2).unwrap();
+        let mut buf = vec![];
+        writer.serialize(&mut buf).unwrap();
+
+        let parsed = TestAnalytics::parse(&buf, 0).unwrap();
+        let mut tests = parsed.tests(0..60, None).unwrap();
+        let abc = tests.next().unwrap().unwrap();
+        assert_eq!(abc.aggregates().total_pass_count, 3);
+        assert!(tests.next().is_none());
+    }
+
+    #[test]
+    fn test_migrate_legacy_format_with_classname() {
+        let mut test = test();
+        test.classname = "tests.test_abc".try_into().unwrap();
+        test.computed_name = "tests/test_abc.py::abc".try_into().unwrap();
+        let mut other = test.clone();
+        other.name = "def".try_into().unwrap();
+
+        let mut writer = TestAnalyticsWriter::new(2);
+        let mut session = writer.start_session(0, CommitHash::default(), &[]);
+        session.insert(&test);
+        session.insert(&other);
+
+        let mut buf = vec![];
+        writer.serialize(&mut buf).unwrap();
+        let parsed = TestAnalytics::parse(&buf, 0).unwrap();
+        let buf_v2 = legacy_buf(&buf, &parsed);
+        let parsed_v2 = TestAnalytics::parse(&buf_v2, 0).unwrap();
+
+        // a migrated record which is not inserted into keeps waiting for its classname
+        let writer = TestAnalyticsWriter::from_existing_format(&parsed_v2).unwrap();
+        let mut buf = vec![];
+        writer.serialize(&mut buf).unwrap();
+        let parsed = TestAnalytics::parse(&buf, 0).unwrap();
+
+        let mut writer = TestAnalyticsWriter::from_existing_format(&parsed).unwrap();
+        let mut session = writer.start_session(DAY, CommitHash::default(), &[]);
+        session.insert(&test);
+
+        let mut buf = vec![];
+        writer.serialize(&mut buf).unwrap();
+        let parsed = TestAnalytics::parse(&buf, DAY).unwrap();
+
+        let mut tests = parsed.find("", "abc", 0..60, None).unwrap();
+        let abc = tests.next().unwrap().unwrap();
+        assert_eq!(abc.classname().unwrap(), "tests.test_abc");
+        assert_eq!(abc.computed_name().unwrap(), "tests/test_abc.py::abc");
+        assert_eq!(abc.aggregates().total_pass_count, 2);
+        assert!(tests.next().is_none());
+
+        let mut tests = parsed.find("", "def", 0..60, None).unwrap();
+        let def = tests.next().unwrap().unwrap();
+        assert_eq!(def.classname().unwrap(), "");
+        assert_eq!(def.aggregates().total_pass_count, 1);
+        assert!(tests.next().is_none());
+
+        // merging the legacy file again merges into the claimed record
+        let writer = TestAnalyticsWriter::merge(&parsed, &parsed_v2).unwrap();
+        let mut buf = vec![];
+        writer.serialize(&mut buf).unwrap();
+        let parsed = TestAnalytics::parse(&buf, DAY).unwrap();
+
+        let mut tests = parsed.find("", "abc", 0..60, None).unwrap();
+        let abc = tests.next().unwrap().unwrap();
+        assert_eq!(abc.classname().unwrap(), "tests.test_abc");
+        assert_eq!(abc.aggregates().total_pass_count, 3);
+        assert!(tests.next().is_none());
+    }
+
     #[test]
     fn test_flags() {
         let test = test();
@@ -443,4 +915,64 @@ mod tests {
         assert_eq!(abc.aggregates().failing_commits, 4); // commit 1 - 4
         assert!(tests.next().is_none());
     }
+
+    #[test]
+    fn test_import_historic() {
+        let commit_1 = CommitHash([0; 20]);
+        let commit_2 = CommitHash([1; 20]);
+        let entry =
+            |timestamp, total_pass_count, failing_commits: &[CommitHash]| HistoricTestData {
+                timestamp,
+                name: "abc".into(),
+                total_pass_count,
+                total_fail_count: failing_commits.len() as u16,
+                total_duration: 2.0,
+                failing_commits: failing_commits.into(),
+                ..Default::default()
+            };
+
+        let mut writer = TestAnalyticsWriter::new(4);
+        // the data is imported out of order, and in multiple batches
+        writer.import_historic(&[
+            entry(3 * DAY, 1, &[]),
+            entry(DAY, 2, &[commit_1]),
+            entry(2 * DAY, 3, &[commit_2]),
+            entry(3 * DAY, 1, &[]),
+        ]);
+        // this is being shifted out by the newer data
+        writer.import_historic(&[entry(0, 5, &[])]);
+        // the second entry is older than what the file retains, so it is ignored
+        writer.import_historic(&[entry(4 * DAY, 7, &[]), entry(0, 100, &[])]);
+
+        let mut buf = vec![];
+        writer.serialize(&mut buf).unwrap();
+
+        let parsed = TestAnalytics::parse(&buf, 4 * DAY).unwrap();
+
+        let mut tests = parsed.tests(0..1, None).unwrap();
+        let abc = tests.next().unwrap().unwrap();
+        assert_eq!(abc.aggregates().total_pass_count, 7);
+        assert!(tests.next().is_none());
+
+        let mut tests = parsed.tests(1..2, None).unwrap();
+        let abc = tests.next().unwrap().unwrap();
+        assert_eq!(abc.aggregates().total_pass_count, 2);
+        assert!(tests.next().is_none());
+
+        let mut tests = parsed.tests(3..4, None).unwrap();
+        let abc = tests.next().unwrap().unwrap();
+        let aggregates = abc.aggregates();
+        assert_eq!(aggregates.total_pass_count, 2);
+        assert_eq!(aggregates.total_fail_count, 1);
+        assert_eq!(aggregates.failing_commits, 1);
+        assert!(tests.next().is_none());
+
+        let mut tests = parsed.tests(0..60, None).unwrap();
+        let abc = tests.next().unwrap().unwrap();
+        let aggregates = abc.aggregates();
+        assert_eq!(aggregates.total_pass_count, 7 + 2 + 3 + 2);
+        assert_eq!(aggregates.total_fail_count, 2);
+        assert_eq!(aggregates.failing_commits, 2);
+        assert!(tests.next().is_none());
+    }
 }
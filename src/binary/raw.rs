@@ -32,6 +32,10 @@ pub struct Test {
     pub testsuite_offset: u32,
     /// Offset of the Test name within the string table.
     pub name_offset: u32,
+    /// Offset of the Test classname within the string table.
+    pub classname_offset: u32,
+    /// Offset of the computed name of the Test within the string table.
+    pub computed_name_offset: u32,
     /// Offset of the Flag Set within the `FlagsSet` table.
     pub flag_set_offset: u32,
     /// The number of valid data entries.
@@ -39,6 +43,35 @@ pub struct Test {
 }
 unsafe impl Pod for Test {}
 
+/// The [`Test`] record of format versions 1 and 2, which lacks the classname
+/// and computed name.
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct LegacyTest {
+    pub testsuite_offset: u32,
+    pub name_offset: u32,
+    pub flag_set_offset: u32,
+    pub valid_data: u32,
+}
+unsafe impl Pod for LegacyTest {}
+
+/// The string offset used for the classname and computed name of tests which
+/// were migrated from a [`LegacyTest`], and thus do not have one.
+pub const NO_STRING: u32 = u32::MAX;
+
+impl From<LegacyTest> for Test {
+    fn from(test: LegacyTest) -> Self {
+        Self {
+            testsuite_offset: test.testsuite_offset,
+            name_offset: test.name_offset,
+            classname_offset: NO_STRING,
+            computed_name_offset: NO_STRING,
+            flag_set_offset: test.flag_set_offset,
+            valid_data: test.valid_data,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, Default)]
 #[repr(C)]
 pub struct TestData {
@@ -71,9 +104,12 @@ mod tests {
         assert_eq!(mem::size_of::<Header>(), 32);
         assert_eq!(mem::align_of::<Header>(), 4);
 
-        assert_eq!(mem::size_of::<Test>(), 16);
+        assert_eq!(mem::size_of::<Test>(), 24);
         assert_eq!(mem::align_of::<Test>(), 4);
 
+        assert_eq!(mem::size_of::<LegacyTest>(), 16);
+        assert_eq!(mem::align_of::<LegacyTest>(), 4);
+
         assert_eq!(mem::size_of::<TestData>(), 24);
         assert_eq!(mem::align_of::<TestData>(), 4);
 
@@ -4,11 +4,14 @@ use std::mem;
 
 use commithashes_set::CommitHashesSet;
 use flags_set::FlagsSet;
+use format::read_string;
+use import::HistoricTestData;
 use indexmap::IndexMap;
 use raw::TestData;
 use timestamps::{adjust_selection_range, offset_from_today, shift_data};
 use watto::{Pod, StringTable};
 
+use crate::compute_name::split_parameters;
 use crate::testrun;
 
 use super::*;
@@ -25,53 +28,29 @@ impl InsertSession<'_> {
     /// Writes the data for the given [`Testrun`](testrun::Testrun) into the
     /// underlying [`TestAnalyticsWriter`].
     pub fn insert(&mut self, test: &testrun::Testrun) {
+        let (name, computed_name) = match (self.writer.aggregation, &test.base_computed_name) {
+            (Aggregation::PerBaseTest, Some(base_computed_name)) => {
+                let base_name = split_parameters(&test.name, test.framework)
+                    .map_or(&*test.name, |(base_name, _)| base_name);
+                (base_name, &**base_computed_name)
+            }
+            _ => (&*test.name, &*test.computed_name),
+        };
+
         let testsuite_offset = self.writer.string_table.insert(&test.testsuite) as u32;
-        let name_offset = self.writer.string_table.insert(&test.name) as u32;
+        let name_offset = self.writer.string_table.insert(name) as u32;
+        let classname_offset = self.writer.string_table.insert(&test.classname) as u32;
+        let computed_name_offset = self.writer.string_table.insert(computed_name) as u32;
         let key = TestKey {
             testsuite_offset,
             name_offset,
+            classname_offset,
+            computed_name_offset,
             flag_set_offset: self.flag_set_offset,
         };
-        let value = raw::Test {
-            testsuite_offset,
-            name_offset,
-            flag_set_offset: self.flag_set_offset,
-            valid_data: 1,
+        let Some(data_idx) = self.writer.data_idx_for(key, self.timestamp) else {
+            return;
         };
-        let (idx, replaced) = self.writer.tests.insert_full(key, value);
-
-        let mut data_idx = idx * self.writer.num_days;
-        if replaced.is_none() {
-            let expected_size = self.writer.tests.len() * self.writer.num_days;
-            self.writer
-                .testdata
-                .resize_with(expected_size, TestData::default);
-        } else {
-            let latest_timestamp = self.writer.testdata[data_idx].last_timestamp;
-
-            if latest_timestamp < self.timestamp {
-                // we are inserting newer data, so shift the existing data around
-                let today_offset = offset_from_today(latest_timestamp, self.timestamp);
-
-                let range = data_idx..data_idx + self.writer.num_days;
-                shift_data(&mut self.writer.testdata[range], today_offset);
-                extend_valid_data(
-                    &mut self.writer.tests[idx].valid_data,
-                    today_offset,
-                    self.writer.num_days,
-                );
-            } else {
-                // otherwise, we are inserting historic data, so adjust our `data_idx` accordingly
-                let today_offset = offset_from_today(self.timestamp, latest_timestamp);
-                if today_offset >= self.writer.num_days {
-                    return;
-                }
-                data_idx += today_offset;
-                self.writer.tests[idx].valid_data = self.writer.tests[idx]
-                    .valid_data
-                    .max(1 + today_offset as u32);
-            }
-        }
 
         let testdata = &mut self.writer.testdata[data_idx];
         testdata.total_duration += test.duration.unwrap_or_default() as f32;
@@ -83,7 +62,12 @@ impl InsertSession<'_> {
 
         match test.outcome {
             testrun::Outcome::Skip => testdata.total_skip_count += 1,
-            testrun::Outcome::Pass => testdata.total_pass_count += 1,
+            testrun::Outcome::Pass => {
+                testdata.total_pass_count += 1;
+                if test.flaky {
+                    testdata.total_flaky_fail_count += 1;
+                }
+            }
             testrun::Outcome::Error | testrun::Outcome::Failure => {
                 testdata.total_fail_count += 1;
                 testdata.failing_commits_set = self
@@ -99,17 +83,65 @@ fn extend_valid_data(valid_data: &mut u32, offset: usize, num_days: usize) {
     *valid_data = (*valid_data as usize + offset).min(num_days) as u32;
 }
 
+/// A test identified by its testsuite and name, regardless of its classname.
+type TestName<'a> = (&'a str, &'a str);
+
+/// A test identified by its testsuite, name, classname and computed name.
+type FullTestName<'a> = (&'a str, &'a str, &'a str, &'a str);
+
+/// A predicate over the testsuite, name and flags of a test.
+type TestPredicate<'a> = dyn FnMut(&str, &str, &[&str]) -> bool + 'a;
+
+/// Changes applied to all the test records by [`TestAnalyticsWriter::compact`].
+#[derive(Default)]
+struct Compaction<'a> {
+    /// Whether each record is still live, or should be thrown away.
+    record_liveness: Option<Vec<bool>>,
+    /// Removes the tests matching this predicate.
+    remove_test: Option<&'a mut TestPredicate<'a>>,
+    /// Renames a test, identified by its testsuite and name, to a test identified by
+    /// its testsuite, name, classname and computed name.
+    rename_test: Option<(TestName<'a>, FullTestName<'a>)>,
+    /// Renames a flag, or removes it when the new name is `None`.
+    edit_flag: Option<(&'a str, Option<&'a str>)>,
+}
+
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 struct TestKey {
     pub testsuite_offset: u32,
     pub name_offset: u32,
+    pub classname_offset: u32,
+    pub computed_name_offset: u32,
     pub flag_set_offset: u32,
 }
 
+impl TestKey {
+    /// The key of a record migrated from a legacy format, which matches this one.
+    fn legacy(self) -> Self {
+        Self {
+            classname_offset: raw::NO_STRING,
+            computed_name_offset: raw::NO_STRING,
+            ..self
+        }
+    }
+}
+
+/// How the runs of parameterized tests are aggregated by the [`TestAnalyticsWriter`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Aggregation {
+    /// Each parameter set is aggregated as a test of its own.
+    #[default]
+    PerParameterSet,
+    /// All the parameter sets are aggregated into their base test, using its
+    /// `base_computed_name`.
+    PerBaseTest,
+}
+
 /// The [`TestAnalytics`] File Writer.
 #[derive(Debug)]
 pub struct TestAnalyticsWriter {
     num_days: usize,
+    aggregation: Aggregation,
 
     string_table: StringTable,
     flags_set: FlagsSet<'static>,
@@ -126,6 +158,7 @@ impl TestAnalyticsWriter {
     pub fn new(num_days: usize) -> Self {
         Self {
             num_days,
+            aggregation: Aggregation::default(),
 
             string_table: StringTable::default(),
             flags_set: FlagsSet::default(),
@@ -138,6 +171,11 @@ impl TestAnalyticsWriter {
         }
     }
 
+    /// Sets how the runs of parameterized tests are aggregated by future insertions.
+    pub fn set_aggregation(&mut self, aggregation: Aggregation) {
+        self.aggregation = aggregation;
+    }
+
     /// Creates an insertion session which allows inserting test run results.
     pub fn start_session(
         &mut self,
@@ -156,24 +194,182 @@ impl TestAnalyticsWriter {
         }
     }
 
+    /// Looks up or creates the record for `key`, returning its index and whether it is new.
+    ///
+    /// A record migrated from a legacy format lacks a classname and computed name, and
+    /// is claimed by the first record with the same testsuite, name and flags.
+    fn record_idx_for(&mut self, key: TestKey) -> (usize, bool) {
+        if let Some(idx) = self.tests.get_index_of(&key) {
+            return (idx, false);
+        }
+
+        let legacy_key = key.legacy();
+        if key != legacy_key {
+            if let Some(idx) = self.tests.get_index_of(&legacy_key) {
+                return (self.rekey_record(idx, key), false);
+            }
+        }
+
+        let (idx, _) = self.tests.insert_full(
+            key,
+            raw::Test {
+                testsuite_offset: key.testsuite_offset,
+                name_offset: key.name_offset,
+                classname_offset: key.classname_offset,
+                computed_name_offset: key.computed_name_offset,
+                flag_set_offset: key.flag_set_offset,
+                valid_data: 1,
+            },
+        );
+        (idx, true)
+    }
+
+    /// Changes the key of the record at `idx`, moving it to the end of the records.
+    ///
+    /// The last record takes the place of the moved record, along with its data.
+    fn rekey_record(&mut self, idx: usize, key: TestKey) -> usize {
+        let last = self.tests.len() - 1;
+        let (_, mut test) = self
+            .tests
+            .swap_remove_index(idx)
+            .expect("the record to rekey exists");
+        test.classname_offset = key.classname_offset;
+        test.computed_name_offset = key.computed_name_offset;
+        self.tests.insert(key, test);
+
+        if idx != last {
+            let (moved, last_data) = self.testdata.split_at_mut(last * self.num_days);
+            let moved = &mut moved[idx * self.num_days..(idx + 1) * self.num_days];
+            moved.swap_with_slice(&mut last_data[..self.num_days]);
+        }
+        last
+    }
+
+    /// Looks up or creates the record for `key`, and returns the index of the
+    /// [`TestData`] bucket that `timestamp` falls into.
+    ///
+    /// Newer data shifts the existing buckets around, whereas older data is
+    /// placed into the matching historic bucket. Returns `None` in case the
+    /// `timestamp` is too far in the past to be recorded.
+    fn data_idx_for(&mut self, key: TestKey, timestamp: u32) -> Option<usize> {
+        let (idx, is_new) = self.record_idx_for(key);
+
+        let mut data_idx = idx * self.num_days;
+        if is_new {
+            let expected_size = self.tests.len() * self.num_days;
+            self.testdata.resize_with(expected_size, TestData::default);
+            self.testdata[data_idx].last_timestamp = timestamp;
+        } else {
+            let latest_timestamp = self.testdata[data_idx].last_timestamp;
+
+            if latest_timestamp < timestamp {
+                // we are inserting newer data, so shift the existing data around
+                let today_offset = offset_from_today(latest_timestamp, timestamp);
+
+                let range = data_idx..data_idx + self.num_days;
+                shift_data(&mut self.testdata[range], today_offset);
+                extend_valid_data(&mut self.tests[idx].valid_data, today_offset, self.num_days);
+            } else {
+                // otherwise, we are inserting historic data, so adjust our `data_idx` accordingly
+                let today_offset = offset_from_today(timestamp, latest_timestamp);
+                if today_offset >= self.num_days {
+                    return None;
+                }
+                data_idx += today_offset;
+                self.tests[idx].valid_data =
+                    self.tests[idx].valid_data.max(1 + today_offset as u32);
+            }
+        }
+
+        Some(data_idx)
+    }
+
+    /// Imports pre-aggregated per-day [`HistoricTestData`] into the writer.
+    ///
+    /// Each entry is written directly into the day bucket matching its `timestamp`,
+    /// so this can be used to seed a file with data from another system.
+    /// Entries that are older than the number of days retained by the writer are ignored.
+    pub fn import_historic<'a>(
+        &mut self,
+        historic_data: impl IntoIterator<Item = &'a HistoricTestData>,
+    ) {
+        let mut flags = vec![];
+        for entry in historic_data {
+            self.timestamp = self.timestamp.max(entry.timestamp);
+
+            flags.clear();
+            flags.extend(entry.flags.iter().map(String::as_str));
+            let flag_set_offset = self.flags_set.insert(&mut self.string_table, &flags);
+
+            let testsuite_offset = self.string_table.insert(&entry.testsuite) as u32;
+            let name_offset = self.string_table.insert(&entry.name) as u32;
+            let classname_offset = self.string_table.insert(&entry.classname) as u32;
+            let computed_name_offset = self.string_table.insert(&entry.computed_name) as u32;
+            let key = TestKey {
+                testsuite_offset,
+                name_offset,
+                classname_offset,
+                computed_name_offset,
+                flag_set_offset,
+            };
+            let Some(data_idx) = self.data_idx_for(key, entry.timestamp) else {
+                continue;
+            };
+
+            let testdata = &mut self.testdata[data_idx];
+            testdata.total_pass_count = testdata
+                .total_pass_count
+                .saturating_add(entry.total_pass_count);
+            testdata.total_fail_count = testdata
+                .total_fail_count
+                .saturating_add(entry.total_fail_count);
+            testdata.total_skip_count = testdata
+                .total_skip_count
+                .saturating_add(entry.total_skip_count);
+            testdata.total_flaky_fail_count = testdata
+                .total_flaky_fail_count
+                .saturating_add(entry.total_flaky_fail_count);
+            testdata.total_duration += entry.total_duration;
+
+            if testdata.last_timestamp <= entry.timestamp {
+                testdata.last_timestamp = entry.timestamp;
+                testdata.last_duration = entry.last_duration;
+            }
+
+            if !entry.failing_commits.is_empty() {
+                testdata.failing_commits_set = self
+                    .commithashes_set
+                    .append_intersection(testdata.failing_commits_set, &entry.failing_commits);
+            }
+        }
+    }
+
     /// Turns an existing parsed [`TestAnalytics`] file into a writer.
+    ///
+    /// Tests migrated from an older format version lack a classname and computed name,
+    /// and are merged into the first test with the same testsuite, name and flags that
+    /// is inserted later on.
     pub fn from_existing_format(data: &TestAnalytics) -> Result<Self, TestAnalyticsError> {
+        let string_table = StringTable::from_bytes(data.string_bytes)
+            .map_err(|_| TestAnalyticsErrorKind::InvalidStringReference)?;
+
         let tests = IndexMap::from_iter(data.tests.iter().map(|test| {
             let key = TestKey {
                 testsuite_offset: test.testsuite_offset,
                 name_offset: test.name_offset,
+                classname_offset: test.classname_offset,
+                computed_name_offset: test.computed_name_offset,
                 flag_set_offset: test.flag_set_offset,
             };
             (key, *test)
         }));
 
-        let string_table = StringTable::from_bytes(data.string_bytes)
-            .map_err(|_| TestAnalyticsErrorKind::InvalidStringReference)?;
         let flags_set = data.flags_set.to_owned();
         let commithashes_set = CommitHashesSet::from_bytes(data.commithashes_bytes)?;
 
         Ok(Self {
             num_days: data.header.num_days as usize,
+            aggregation: Aggregation::default(),
 
             string_table,
             flags_set,
@@ -205,120 +401,182 @@ impl TestAnalyticsWriter {
         let expected_reserve = expected_new * writer.num_days;
         writer.testdata.reserve(expected_reserve);
 
-        let smaller_flags = smaller.flags_set.iter(smaller.string_bytes);
-        let mut flags_mapping = HashMap::with_capacity(smaller_flags.len());
-        for res in smaller_flags {
-            let (smaller_offset, flags) = res?;
-            let larger_offset = writer.flags_set.insert(&mut writer.string_table, &flags);
-            flags_mapping.insert(smaller_offset, larger_offset);
+        writer.merge_from(smaller)?;
+
+        Ok(writer)
+    }
+
+    /// Merges any number of parsed [`TestAnalytics`] into a writer.
+    ///
+    /// As opposed to repeatedly calling [`merge`](Self::merge), this builds a single
+    /// string table and flag set, and merges all the inputs in one pass.
+    /// The resulting writer retains the largest `num_days` of all the inputs.
+    pub fn merge_all(inputs: &[TestAnalytics]) -> Result<Self, TestAnalyticsError> {
+        let num_days = inputs
+            .iter()
+            .map(|input| input.header.num_days as usize)
+            .max()
+            .unwrap_or_default();
+        let mut writer = Self::new(num_days);
+        writer.timestamp = inputs
+            .iter()
+            .map(|input| input.timestamp)
+            .max()
+            .unwrap_or_default();
+
+        // assume that the largest input contains most of the unique entries:
+        let expected_tests = inputs
+            .iter()
+            .map(|input| input.header.num_tests as usize)
+            .max()
+            .unwrap_or_default();
+        writer.tests.reserve(expected_tests);
+        writer.testdata.reserve(expected_tests * writer.num_days);
+
+        for input in inputs {
+            writer.merge_from(input)?;
         }
 
-        for (smaller_idx, test) in smaller.tests.iter().enumerate() {
-            let testsuite = StringTable::read(smaller.string_bytes, test.testsuite_offset as usize)
-                .map_err(|_| TestAnalyticsErrorKind::InvalidStringReference)?;
-            let name = StringTable::read(smaller.string_bytes, test.name_offset as usize)
-                .map_err(|_| TestAnalyticsErrorKind::InvalidStringReference)?;
+        Ok(writer)
+    }
+
+    /// Merges all the records from `other` into the existing records of the writer.
+    fn merge_from(&mut self, other: &TestAnalytics) -> Result<(), TestAnalyticsError> {
+        let other_flags = other.flags_set.iter(other.string_bytes);
+        let mut flags_mapping = HashMap::with_capacity(other_flags.len());
+        for res in other_flags {
+            let (other_offset, flags) = res?;
+            let offset = self.flags_set.insert(&mut self.string_table, &flags);
+            flags_mapping.insert(other_offset, offset);
+        }
+
+        let other_num_days = other.header.num_days as usize;
+        // the existing records which the migrated records of `other` are merged into
+        let mut same_tests: Option<HashMap<TestKey, TestKey>> = None;
 
-            let testsuite_offset = writer.string_table.insert(testsuite) as u32;
-            let name_offset = writer.string_table.insert(name) as u32;
+        for (other_idx, test) in other.tests.iter().enumerate() {
+            let testsuite_offset = self.copy_string(other.string_bytes, test.testsuite_offset)?;
+            let name_offset = self.copy_string(other.string_bytes, test.name_offset)?;
+            let classname_offset = self.copy_string(other.string_bytes, test.classname_offset)?;
+            let computed_name_offset =
+                self.copy_string(other.string_bytes, test.computed_name_offset)?;
             let flag_set_offset = *flags_mapping
                 .get(&test.flag_set_offset)
                 .ok_or(TestAnalyticsErrorKind::InvalidFlagSetReference)?;
 
-            let key = TestKey {
-                testsuite_offset,
-                name_offset,
-                flag_set_offset,
-            };
-            let value = raw::Test {
+            let mut key = TestKey {
                 testsuite_offset,
                 name_offset,
+                classname_offset,
+                computed_name_offset,
                 flag_set_offset,
-                valid_data: 1,
             };
-            let (idx, replaced) = writer.tests.insert_full(key, value);
+            if key == key.legacy() {
+                let same_tests = same_tests.get_or_insert_with(|| {
+                    let mut same_tests = HashMap::new();
+                    for existing in self.tests.keys() {
+                        same_tests.entry(existing.legacy()).or_insert(*existing);
+                    }
+                    same_tests
+                });
+                if let Some(existing) = same_tests.get(&key) {
+                    key = *existing;
+                }
+            }
+            let other_idx = other_idx * other_num_days;
+            let other_data = &other.testdata[other_idx..other_idx + other_num_days];
+            self.merge_record(key, other_data, other.commithashes_bytes)?;
+        }
 
-            let data_idx = idx * writer.num_days;
-            let smaller_idx = smaller_idx * smaller.header.num_days as usize;
-            let smaller_timestamp = smaller.testdata[smaller_idx].last_timestamp;
+        Ok(())
+    }
 
-            let larger_timestamp = if replaced.is_none() {
-                let expected_size = writer.tests.len() * writer.num_days;
-                writer
-                    .testdata
-                    .resize_with(expected_size, TestData::default);
+    /// Copies the string at `offset` into the string table of the writer, keeping the
+    /// [`raw::NO_STRING`] of migrated records.
+    fn copy_string(&mut self, string_bytes: &[u8], offset: u32) -> Result<u32, TestAnalyticsError> {
+        if offset == raw::NO_STRING {
+            return Ok(raw::NO_STRING);
+        }
+        let string = read_string(string_bytes, offset)?;
+        Ok(self.string_table.insert(string) as u32)
+    }
 
-                smaller_timestamp
-            } else {
-                writer.testdata[data_idx].last_timestamp
-            };
+    /// Merges the `other_data` of a single record into the record identified by `key`.
+    ///
+    /// The `other_data` is aligned to the existing data based on the timestamps of both records.
+    fn merge_record(
+        &mut self,
+        key: TestKey,
+        other_data: &[TestData],
+        commithashes_bytes: &[u8],
+    ) -> Result<(), TestAnalyticsError> {
+        let (idx, is_new) = self.record_idx_for(key);
 
-            let (smaller_range, today_offset) = if smaller_timestamp > larger_timestamp {
-                // smaller has more recent data buckets, so we shift things around:
-                let today_offset = offset_from_today(larger_timestamp, smaller_timestamp);
-                let range = data_idx..data_idx + writer.num_days;
-
-                shift_data(&mut writer.testdata[range], today_offset);
-                extend_valid_data(
-                    &mut writer.tests[idx].valid_data,
-                    today_offset,
-                    writer.num_days,
-                );
-
-                let smaller_range = adjust_selection_range(
-                    smaller_idx..smaller_idx + smaller.header.num_days as usize,
-                    0..writer.num_days,
-                    today_offset,
-                );
-                (smaller_range, 0)
-            } else {
-                let today_offset = offset_from_today(smaller_timestamp, larger_timestamp);
-                let smaller_range = adjust_selection_range(
-                    smaller_idx..smaller_idx + smaller.header.num_days as usize,
-                    0..writer.num_days,
-                    today_offset,
-                );
-
-                (smaller_range, today_offset)
-            };
+        let data_idx = idx * self.num_days;
+        let other_timestamp = other_data[0].last_timestamp;
 
-            let overlap_len = smaller_range.end - smaller_range.start;
-            let idx_start = data_idx + today_offset;
-            let larger_range = idx_start..idx_start + overlap_len;
+        let existing_timestamp = if is_new {
+            let expected_size = self.tests.len() * self.num_days;
+            self.testdata.resize_with(expected_size, TestData::default);
 
-            writer.tests[idx].valid_data = writer.tests[idx]
-                .valid_data
-                .max((larger_range.end - data_idx) as u32)
-                .min(writer.num_days as u32);
+            other_timestamp
+        } else {
+            self.testdata[data_idx].last_timestamp
+        };
 
-            let commithashes_bytes = smaller.commithashes_bytes;
+        let (other_range, today_offset) = if other_timestamp > existing_timestamp {
+            // other has more recent data buckets, so we shift things around:
+            let today_offset = offset_from_today(existing_timestamp, other_timestamp);
+            let range = data_idx..data_idx + self.num_days;
 
-            let larger_data = &mut writer.testdata[larger_range];
-            let smaller_data = &smaller.testdata[smaller_range];
+            shift_data(&mut self.testdata[range], today_offset);
+            extend_valid_data(&mut self.tests[idx].valid_data, today_offset, self.num_days);
 
-            for (larger, smaller) in larger_data.iter_mut().zip(smaller_data) {
-                larger.total_pass_count += smaller.total_pass_count;
-                larger.total_fail_count += smaller.total_fail_count;
-                larger.total_skip_count += smaller.total_skip_count;
-                larger.total_flaky_fail_count += smaller.total_flaky_fail_count;
-                larger.total_duration += smaller.total_duration;
+            let other_range =
+                adjust_selection_range(0..other_data.len(), 0..self.num_days, today_offset);
+            (other_range, 0)
+        } else {
+            let today_offset = offset_from_today(other_timestamp, existing_timestamp);
+            let other_range =
+                adjust_selection_range(0..other_data.len(), 0..self.num_days, today_offset);
 
-                if smaller.last_timestamp >= larger.last_timestamp {
-                    larger.last_timestamp = smaller.last_timestamp;
-                    larger.last_duration = smaller.last_duration;
-                }
+            (other_range, today_offset)
+        };
 
-                let smaller_failing_commits =
-                    CommitHashesSet::read_raw(commithashes_bytes, smaller.failing_commits_set)?;
-                if !smaller_failing_commits.is_empty() {
-                    larger.failing_commits_set = writer
-                        .commithashes_set
-                        .append_intersection(larger.failing_commits_set, smaller_failing_commits);
-                }
+        let overlap_len = other_range.end - other_range.start;
+        let idx_start = data_idx + today_offset;
+        let existing_range = idx_start..idx_start + overlap_len;
+
+        self.tests[idx].valid_data = self.tests[idx]
+            .valid_data
+            .max((existing_range.end - data_idx) as u32)
+            .min(self.num_days as u32);
+
+        let existing_data = &mut self.testdata[existing_range];
+        let other_data = &other_data[other_range];
+
+        for (existing, other) in existing_data.iter_mut().zip(other_data) {
+            existing.total_pass_count += other.total_pass_count;
+            existing.total_fail_count += other.total_fail_count;
+            existing.total_skip_count += other.total_skip_count;
+            existing.total_flaky_fail_count += other.total_flaky_fail_count;
+            existing.total_duration += other.total_duration;
+
+            if other.last_timestamp >= existing.last_timestamp {
+                existing.last_timestamp = other.last_timestamp;
+                existing.last_duration = other.last_duration;
+            }
+
+            let other_failing_commits =
+                CommitHashesSet::read_raw(commithashes_bytes, other.failing_commits_set)?;
+            if !other_failing_commits.is_empty() {
+                existing.failing_commits_set = self
+                    .commithashes_set
+                    .append_intersection(existing.failing_commits_set, other_failing_commits);
             }
         }
 
-        Ok(writer)
+        Ok(())
     }
 
     /// Does garbage collection by rewriting test records and throwing away those with expired data.
@@ -328,7 +586,7 @@ impl TestAnalyticsWriter {
     /// exceeds `threshold`, which defaults to 25% of the records.
     pub fn rewrite(
         &mut self,
-        mut num_days: usize,
+        num_days: usize,
         timestamp: u32,
         garbage_threshold: Option<usize>,
     ) -> Result<bool, TestAnalyticsError> {
@@ -352,77 +610,185 @@ impl TestAnalyticsWriter {
             return Ok(false);
         }
 
+        self.compact(
+            num_days,
+            Compaction {
+                record_liveness: Some(record_liveness),
+                ..Default::default()
+            },
+        )?;
+
+        Ok(true)
+    }
+
+    /// Removes all the tests for which `predicate` returns `true`.
+    ///
+    /// The `predicate` is called with the testsuite, name and flags of each test.
+    pub fn remove_tests(
+        &mut self,
+        mut predicate: impl FnMut(&str, &str, &[&str]) -> bool,
+    ) -> Result<(), TestAnalyticsError> {
+        self.compact(
+            self.num_days,
+            Compaction {
+                remove_test: Some(&mut predicate),
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Renames the test identified by `testsuite` and `name`, regardless of its classname.
+    ///
+    /// The renamed test takes on the new classname and computed name as well, so that
+    /// it is merged with an already existing test of that name, and with the runs of the
+    /// new name inserted later on.
+    pub fn rename_test(
+        &mut self,
+        testsuite: &str,
+        name: &str,
+        new_testsuite: &str,
+        new_name: &str,
+        new_classname: &str,
+        new_computed_name: &str,
+    ) -> Result<(), TestAnalyticsError> {
+        self.compact(
+            self.num_days,
+            Compaction {
+                rename_test: Some((
+                    (testsuite, name),
+                    (new_testsuite, new_name, new_classname, new_computed_name),
+                )),
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Removes the given `flag` from all the tests.
+    ///
+    /// Tests which end up with the same set of flags are merged.
+    pub fn remove_flag(&mut self, flag: &str) -> Result<(), TestAnalyticsError> {
+        self.compact(
+            self.num_days,
+            Compaction {
+                edit_flag: Some((flag, None)),
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Renames the given `flag` on all the tests.
+    ///
+    /// Tests which end up with the same set of flags are merged.
+    pub fn rename_flag(&mut self, flag: &str, new_flag: &str) -> Result<(), TestAnalyticsError> {
+        self.compact(
+            self.num_days,
+            Compaction {
+                edit_flag: Some((flag, Some(new_flag))),
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Rewrites all the test records into a fresh string table and flag set,
+    /// applying the given [`Compaction`] along the way.
+    ///
+    /// Records which end up with the same [`TestKey`] are merged.
+    fn compact(
+        &mut self,
+        mut num_days: usize,
+        mut compaction: Compaction,
+    ) -> Result<(), TestAnalyticsError> {
         mem::swap(&mut num_days, &mut self.num_days);
         let string_table = mem::take(&mut self.string_table);
         let flags_set = mem::take(&mut self.flags_set);
         let tests = mem::take(&mut self.tests);
         let testdata = mem::take(&mut self.testdata);
         let commithashes_set = mem::replace(&mut self.commithashes_set, CommitHashesSet::new());
+        let commithashes_bytes = commithashes_set.into_bytes();
 
         let mut flags_mapping = HashMap::with_capacity(flags_set.map.len());
 
-        let expected_size = live_records * self.num_days;
-        self.tests.reserve(live_records);
-        self.testdata.reserve(expected_size);
+        let expected_records = match &compaction.record_liveness {
+            Some(liveness) => liveness.iter().filter(|live| **live).count(),
+            None => tests.len(),
+        };
+        self.tests.reserve(expected_records);
+        self.testdata.reserve(expected_records * self.num_days);
 
-        for ((old_idx, test), record_live) in tests.values().enumerate().zip(record_liveness) {
-            if !record_live {
-                continue;
+        for (old_idx, test) in tests.values().enumerate() {
+            if let Some(liveness) = &compaction.record_liveness {
+                if !liveness[old_idx] {
+                    continue;
+                }
+            }
+
+            let testsuite =
+                StringTable::read(string_table.as_bytes(), test.testsuite_offset as usize)
+                    .map_err(|_| TestAnalyticsErrorKind::InvalidStringReference)?;
+            let name = StringTable::read(string_table.as_bytes(), test.name_offset as usize)
+                .map_err(|_| TestAnalyticsErrorKind::InvalidStringReference)?;
+
+            if let Some(predicate) = &mut compaction.remove_test {
+                let flags = flags_set.resolve(string_table.as_bytes(), test.flag_set_offset)?;
+                if predicate(testsuite, name, &flags) {
+                    continue;
+                }
             }
 
             let flag_set_offset = match flags_mapping.entry(test.flag_set_offset) {
                 hash_map::Entry::Occupied(occupied_entry) => *occupied_entry.get(),
                 hash_map::Entry::Vacant(vacant_entry) => {
-                    let flags = flags_set.resolve(string_table.as_bytes(), test.flag_set_offset)?;
+                    let mut flags =
+                        flags_set.resolve(string_table.as_bytes(), test.flag_set_offset)?;
+                    if let Some((flag, new_flag)) = compaction.edit_flag {
+                        if let Some(pos) = flags.iter().position(|f| *f == flag) {
+                            match new_flag {
+                                Some(new_flag) => flags[pos] = new_flag,
+                                None => {
+                                    flags.remove(pos);
+                                }
+                            }
+                        }
+                    }
                     let flag_set_offset = self.flags_set.insert(&mut self.string_table, &flags);
 
                     *vacant_entry.insert(flag_set_offset)
                 }
             };
 
-            let testsuite =
-                StringTable::read(string_table.as_bytes(), test.testsuite_offset as usize)
-                    .map_err(|_| TestAnalyticsErrorKind::InvalidStringReference)?;
-            let name = StringTable::read(string_table.as_bytes(), test.name_offset as usize)
-                .map_err(|_| TestAnalyticsErrorKind::InvalidStringReference)?;
-
-            let testsuite_offset = self.string_table.insert(testsuite) as u32;
-            let name_offset = self.string_table.insert(name) as u32;
+            let (testsuite_offset, name_offset, classname_offset, computed_name_offset) =
+                match compaction.rename_test {
+                    Some((from, (new_testsuite, new_name, new_classname, new_computed_name)))
+                        if from == (testsuite, name) =>
+                    {
+                        (
+                            self.string_table.insert(new_testsuite) as u32,
+                            self.string_table.insert(new_name) as u32,
+                            self.string_table.insert(new_classname) as u32,
+                            self.string_table.insert(new_computed_name) as u32,
+                        )
+                    }
+                    _ => (
+                        self.string_table.insert(testsuite) as u32,
+                        self.string_table.insert(name) as u32,
+                        self.copy_string(string_table.as_bytes(), test.classname_offset)?,
+                        self.copy_string(string_table.as_bytes(), test.computed_name_offset)?,
+                    ),
+                };
             let key = TestKey {
                 testsuite_offset,
                 name_offset,
+                classname_offset,
+                computed_name_offset,
                 flag_set_offset,
             };
-            let value = raw::Test {
-                testsuite_offset,
-                name_offset,
-                flag_set_offset,
-                valid_data: test.valid_data.max(num_days as u32),
-            };
-            let (_new_idx, replaced) = self.tests.insert_full(key, value);
-            assert!(replaced.is_none()); // the records are already unique, and we re-insert those
 
-            let overlap_days = num_days.min(self.num_days);
             let old_idx = old_idx * num_days;
-
-            let old_range = old_idx..old_idx + overlap_days;
-            self.testdata.extend(testdata[old_range].iter().map(|data| {
-                let failing_commits = commithashes_set.read(data.failing_commits_set);
-                let failing_commits_set = self
-                    .commithashes_set
-                    .append_intersection(0, failing_commits);
-
-                TestData {
-                    failing_commits_set,
-                    ..*data
-                }
-            }));
-
-            let expected_size = self.tests.len() * self.num_days;
-            self.testdata.resize_with(expected_size, TestData::default);
+            let old_data = &testdata[old_idx..old_idx + num_days];
+            self.merge_record(key, old_data, &commithashes_bytes)?;
         }
 
-        Ok(true)
+        Ok(())
     }
 
     /// Serialize the converted data.
@@ -448,6 +814,28 @@ impl TestAnalyticsWriter {
             string_bytes: string_bytes.len() as u32,
         };
 
+        // the index sorts all the tests by testsuite and name, then by classname and
+        // computed name, and then by flags
+        let mut index: Vec<u32> = (0..self.tests.len() as u32).collect();
+        let sort_key = |test: &raw::Test| {
+            // the offsets were all created by this writer, so they are valid
+            let testsuite =
+                StringTable::read(&string_bytes, test.testsuite_offset as usize).unwrap_or("");
+            let name = StringTable::read(&string_bytes, test.name_offset as usize).unwrap_or("");
+            let classname =
+                StringTable::read(&string_bytes, test.classname_offset as usize).unwrap_or("");
+            let computed_name =
+                StringTable::read(&string_bytes, test.computed_name_offset as usize).unwrap_or("");
+            (
+                testsuite,
+                name,
+                classname,
+                computed_name,
+                test.flag_set_offset,
+            )
+        };
+        index.sort_by_cached_key(|idx| sort_key(&self.tests[*idx as usize]));
+
         writer.write_all(header.as_bytes())?;
 
         for test in self.tests.into_values() {
@@ -457,6 +845,7 @@ impl TestAnalyticsWriter {
         writer.write_all(self.testdata.as_bytes())?;
 
         writer.write_all(flags_set_table.as_bytes())?;
+        writer.write_all(index.as_bytes())?;
 
         writer.write_all(&commithashes_bytes)?;
         writer.write_all(&string_bytes)?;
@@ -1,14 +1,77 @@
+use crate::network::Network;
 use crate::testrun::Framework;
 use quick_xml::escape::unescape;
-use std::{borrow::Cow, collections::HashSet};
+use std::borrow::Cow;
 
+fn has_drive_letter(path: &str) -> bool {
+    matches!(path.as_bytes(), [drive, b':', b'\\' | b'/', ..] if drive.is_ascii_alphabetic())
+}
+
+/// Whether `path` was written on Windows, using `\` as the separator or starting
+/// with a drive letter.
+fn is_windows_path(path: &str) -> bool {
+    path.contains('\\') || has_drive_letter(path)
+}
+
+/// Normalizes a path written on Windows, using `/` as the separator and removing the
+/// drive letter, so that it matches the same path written on other platforms.
+pub fn normalize_path(path: &str) -> Cow<'_, str> {
+    let path = if has_drive_letter(path) {
+        &path[2..]
+    } else {
+        path
+    };
+    if path.contains('\\') {
+        Cow::Owned(path.replace('\\', "/"))
+    } else {
+        Cow::Borrowed(path)
+    }
+}
+
+/// Returns the number of leading `classname_components` which make up the module path,
+/// being the trailing components of the `path_components` without the `.py` extension.
+///
+/// The components are compared ignoring their case, as paths on Windows are case-insensitive.
+fn module_path_len(path_components: &[&str], classname_components: &[&str]) -> Option<usize> {
+    let (file, dirs) = path_components.split_last()?;
+    let module = match file.len().checked_sub(3) {
+        Some(end) if file.is_char_boundary(end) && file[end..].eq_ignore_ascii_case(".py") => {
+            &file[..end]
+        }
+        _ => file,
+    };
+
+    (1..=path_components.len().min(classname_components.len()))
+        .rev()
+        .find(|&len| {
+            let (module_classname, dir_classname) =
+                classname_components[..len].split_last().unwrap();
+            module_classname.eq_ignore_ascii_case(module)
+                && dirs[dirs.len() - (len - 1)..]
+                    .iter()
+                    .zip(dir_classname)
+                    .all(|(dir, component)| dir.eq_ignore_ascii_case(component))
+        })
+}
+
+/// Computes the name of a pytest test from the path of its file.
+///
+/// The classname starts with the module path of the file, which is skipped. For paths
+/// written on Windows, the module path is matched ignoring its case, and spelled like
+/// the classname so that the name matches the one computed on other platforms. Other
+/// paths keep the original scheme of skipping as many components as the path has.
 fn compute_pytest_using_filename(classname: &str, name: &str, filename: &str) -> String {
-    let path_components = filename.split('/').count();
+    let classname_components = classname.split('.').collect::<Vec<_>>();
 
-    let classname_components = classname.split(".");
+    let (filename, module_len) = if is_windows_path(filename) {
+        windows_module_path(&normalize_path(filename), &classname_components)
+    } else {
+        (filename.to_string(), filename.split('/').count())
+    };
 
     let actual_classname = classname_components
-        .skip(path_components)
+        .into_iter()
+        .skip(module_len)
         .collect::<Vec<_>>()
         .join("::");
 
@@ -19,34 +82,256 @@ fn compute_pytest_using_filename(classname: &str, name: &str, filename: &str) ->
     }
 }
 
+/// Returns the normalized `filename` of a pytest test written on Windows, and the
+/// number of classname components which make up its module path.
+fn windows_module_path(filename: &str, classname_components: &[&str]) -> (String, usize) {
+    let path_components = filename.split('/').collect::<Vec<_>>();
+    match module_path_len(&path_components, classname_components) {
+        Some(len) => {
+            // the module path is taken from the classname, so that it is spelled the same
+            // on all platforms, and an absolute path differs between machines anyway
+            let module_path = path_from_classname(&classname_components[..len]);
+            let dirs = &path_components[..path_components.len() - len];
+            if filename.starts_with('/') || dirs.is_empty() {
+                (module_path, len)
+            } else {
+                (format!("{}/{}", dirs.join("/"), module_path), len)
+            }
+        }
+        None => (filename.to_string(), path_components.len()),
+    }
+}
+
 fn path_from_classname(classname: &[&str]) -> String {
     format!("{}.py", classname.join("/"))
 }
 
-fn compute_pytest_using_network(classname: &str, name: &str, network: &HashSet<String>) -> String {
-    let classname_components = classname.split(".").collect::<Vec<_>>();
-    let mut path_component_count = 0;
-    let start = classname_components.len();
-
-    while path_component_count < start {
-        let path = path_from_classname(&classname_components[..start - path_component_count]);
-        if network.contains(&path) {
-            if path_component_count > 0 {
-                let actual_classname = classname_components
-                    .into_iter()
-                    .skip(start - path_component_count)
-                    .collect::<Vec<_>>()
-                    .join("::");
-                return format!("{}::{}::{}", path, actual_classname, name);
-            } else {
-                return format!("{}::{}", path, name);
+fn compute_pytest_using_network(classname: &str, name: &str, network: &Network) -> String {
+    let Some(network_match) = network.resolve(classname) else {
+        return format!("{}::{}", classname, name);
+    };
+
+    let actual_classname = classname
+        .split('.')
+        .skip(network_match.module_len)
+        .collect::<Vec<_>>()
+        .join("::");
+    if actual_classname.is_empty() {
+        format!("{}::{}", network_match.path, name)
+    } else {
+        format!("{}::{}::{}", network_match.path, actual_classname, name)
+    }
+}
+
+/// Whether `name` is an RSpec example id like `./spec/foo_spec.rb[1:2:3]`, which
+/// identifies an example by its position within the nested example groups of a file.
+fn is_rspec_example_id(name: &str) -> bool {
+    name.strip_suffix(']')
+        .and_then(|name| name.rsplit_once(".rb["))
+        .is_some_and(|(_, position)| {
+            !position.is_empty() && position.chars().all(|c| c.is_ascii_digit() || c == ':')
+        })
+}
+
+/// `rspec_junit_formatter` writes the path of the spec file as the classname,
+/// with `/` replaced by `.` and without its extension.
+fn rspec_path_from_classname(classname: &str) -> String {
+    format!("./{}.rb", classname.replace('.', "/"))
+}
+
+fn compute_rspec(classname: &str, name: &str, filename: Option<&str>) -> String {
+    if is_rspec_example_id(name) {
+        return name.to_string();
+    }
+    match filename {
+        Some(filename) => format!("{}::{}", filename, name),
+        None if classname.is_empty() => name.to_string(),
+        None => format!("{}::{}", rspec_path_from_classname(classname), name),
+    }
+}
+
+fn is_index(index: &str) -> bool {
+    !index.is_empty() && index.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Splits a JUnit 5 display name like `[2] 1, 2` into its invocation index and the
+/// arguments of the invocation.
+pub fn split_display_name(name: &str) -> Option<(&str, &str)> {
+    let (index, arguments) = name.strip_prefix('[')?.split_once("] ")?;
+    is_index(index).then_some((index, arguments))
+}
+
+/// Finds the test method of a JUnit 5 test template, like a parameterized test, in the
+/// unique ID which the JUnit Platform writes into the output of each of its invocations,
+/// like `[engine:junit-jupiter]/[class:a.B]/[test-template:add(int)]/[test-template-invocation:#2]`.
+pub fn find_test_template(output: &str) -> Option<&str> {
+    let unique_id = output
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("unique-id: "))?;
+    unique_id
+        .split('/')
+        .find_map(|segment| segment.strip_prefix("[test-template:")?.strip_suffix(']'))
+}
+
+/// Splits the invocation index off the name of a parameterized JVM test.
+///
+/// This understands the `shouldAdd(int, int)[1]` names of Surefire, and the
+/// `adds [a: 1, b: 2, #0]` names of Spock.
+fn split_invocation_index(name: &str) -> Option<(&str, &str)> {
+    let (base, bracketed) = name.strip_suffix(']')?.rsplit_once('[')?;
+    if is_index(bracketed) {
+        return Some((base, bracketed));
+    }
+    let (_, index) = bracketed.rsplit_once('#')?;
+    is_index(index).then_some((base.trim_end(), index))
+}
+
+/// Computes the name of a JUnit, TestNG, Kotest or Spock test.
+///
+/// Nested classes like `Outer$Inner` are written as `Outer.Inner`, and the invocation
+/// index of parameterized tests is always put at the end, like `method(int)[1]`.
+/// JUnit 5 display names like `[1] 1, 2` are kept as they are, as they lack the name
+/// of the test method.
+fn compute_jvm(classname: &str, name: &str) -> String {
+    let name = match split_invocation_index(name) {
+        Some((base, index)) => Cow::Owned(format!("{}[{}]", base, index)),
+        None => Cow::Borrowed(name),
+    };
+    if classname.is_empty() {
+        return name.into_owned();
+    }
+    format!("{}.{}", classname.replace('$', "."), name)
+}
+
+/// Catch2 writes `{binary}.{class}` as the classname, with `global` as the class of
+/// tests which are not defined on a class.
+fn compute_catch2(classname: &str, name: &str) -> String {
+    match classname.rsplit_once('.') {
+        Some((_, "global")) => name.to_string(),
+        Some((_, class)) => format!("{}::{}", class, name),
+        None if classname.is_empty() => name.to_string(),
+        None => format!("{}::{}", classname, name),
+    }
+}
+
+/// Splits the `name` of a parameterized test into the name of its base test and
+/// its parameters, like `test_add` and `1-2` for the pytest test `test_add[1-2]`.
+///
+/// JUnit 5 display names like `[1] 1, 2` do not include the name of the test method,
+/// see [`find_test_template`], and the titles of Jest and Vitest `test.each` tests have
+/// their parameters formatted into them without any delimiter, so neither can be split.
+pub fn split_parameters(name: &str, framework: Option<Framework>) -> Option<(&str, &str)> {
+    let (base, parameters) = match framework? {
+        Framework::Pytest => name.strip_suffix(']')?.split_once('[')?,
+        Framework::JUnit | Framework::TestNG | Framework::Kotest | Framework::Spock => {
+            if split_display_name(name).is_some() {
+                return None;
             }
+            let (base, bracketed) = name.strip_suffix(']')?.rsplit_once('[')?;
+            let is_invocation = is_index(bracketed)
+                || bracketed
+                    .rsplit_once('#')
+                    .is_some_and(|(_, index)| is_index(index));
+            if !is_invocation {
+                return None;
+            }
+            (base.trim_end(), bracketed)
         }
+        _ => return None,
+    };
+    (!base.is_empty() && !parameters.is_empty()).then_some((base, parameters))
+}
+
+/// The separator between the segments of a name hierarchy within a computed name.
+const HIERARCHY_SEPARATOR: &str = " > ";
+
+/// Returns the separator with which a Jest or Vitest reporter joined the titles in `text`.
+///
+/// Vitest joins these with ` > `, and `jest-junit` with its `ancestorSeparator`, which
+/// can only be told apart from the spaces within the titles when set to ` > ` or
+/// Jest's own ` › `.
+fn hierarchy_separator(text: &str, framework: Framework) -> Option<&'static str> {
+    match framework {
+        Framework::Jest if text.contains(" › ") => Some(" › "),
+        Framework::Jest | Framework::Vitest => Some(HIERARCHY_SEPARATOR),
+        _ => None,
+    }
+}
+
+/// Splits the name of a Jest or Vitest test into the titles of its `describe` blocks,
+/// followed by its own title, if the name includes any `describe` blocks.
+pub fn split_hierarchy(name: &str, framework: Option<Framework>) -> Option<Vec<&str>> {
+    let separator = hierarchy_separator(name, framework?)?;
+    let segments = name.split(separator).collect::<Vec<_>>();
+    (segments.len() > 1).then_some(segments)
+}
+
+/// Returns the titles of the `describe` blocks of a Jest or Vitest test, followed by
+/// its own title.
+///
+/// Besides the `name`, these are read from the other attributes written by `jest-junit`:
+/// the `classname` holds the `describe` blocks when it differs from the name, and with
+/// the default templates, the `testsuite` is named after the outermost `describe` block.
+pub fn name_hierarchy<'a>(
+    classname: &'a str,
+    name: &'a str,
+    testsuite: &'a str,
+    framework: Option<Framework>,
+) -> Vec<&'a str> {
+    let Some(separator) = framework.and_then(|framework| hierarchy_separator(name, framework))
+    else {
+        return vec![];
+    };
+
+    if framework == Some(Framework::Jest) && !classname.is_empty() && classname != name {
+        let describes = match classname.strip_suffix(name) {
+            // the `{classname} {title}` template repeats the title in the classname
+            Some(describes) if !describes.trim_end().is_empty() => describes
+                .strip_suffix(separator)
+                .unwrap_or(describes.trim_end()),
+            _ => classname,
+        };
+        let describes_separator = if describes.contains(" › ") {
+            " › "
+        } else {
+            HIERARCHY_SEPARATOR
+        };
+        return describes
+            .split(describes_separator)
+            .chain(name.split(separator))
+            .collect();
+    }
 
-        path_component_count += 1;
+    if let Some(segments) = split_hierarchy(name, framework) {
+        return segments;
+    }
+    if framework == Some(Framework::Jest) && !testsuite.is_empty() {
+        let title = name
+            .strip_prefix(testsuite)
+            .and_then(|title| title.strip_prefix(' '));
+        if let Some(title) = title.filter(|title| !title.is_empty()) {
+            return vec![testsuite, title];
+        }
     }
+    vec![name]
+}
 
-    format!("{}::{}", classname, name)
+/// Joins the `segments` of a name hierarchy, escaping every `\` and `>` within them
+/// with a `\`, so that the separators between the segments remain unambiguous.
+pub fn render_hierarchy<'s>(segments: impl IntoIterator<Item = &'s str>) -> String {
+    let mut rendered = String::new();
+    for (i, segment) in segments.into_iter().enumerate() {
+        if i > 0 {
+            rendered.push_str(HIERARCHY_SEPARATOR);
+        }
+        for c in segment.chars() {
+            if matches!(c, '\\' | '>') {
+                rendered.push('\\');
+            }
+            rendered.push(c);
+        }
+    }
+    rendered
 }
 
 pub fn unescape_str(s: &str) -> Cow<'_, str> {
@@ -58,7 +343,7 @@ pub fn compute_name(
     name: &str,
     framework: Option<Framework>,
     filename: Option<&str>,
-    network: Option<&HashSet<String>>,
+    network: Option<&Network>,
 ) -> String {
     let name = unescape_str(name);
     let classname = unescape_str(classname);
@@ -75,15 +360,20 @@ pub fn compute_name_framework(
     name: &str,
     framework: Framework,
     filename: Option<&str>,
-    network: Option<&HashSet<String>>,
+    network: Option<&Network>,
 ) -> String {
-    let filename = filename.map(|f| unescape_str(f));
+    let raw_filename = filename.map(unescape_str);
+    let filename = raw_filename.as_deref().map(normalize_path);
 
     match framework {
-        Framework::Jest => name.to_string(),
+        Framework::Jest => match split_hierarchy(name, Some(framework)) {
+            Some(segments) => render_hierarchy(segments),
+            // names without any `describe` blocks are kept as they are
+            None => name.to_string(),
+        },
         Framework::Pytest => {
-            if let Some(filename) = filename {
-                compute_pytest_using_filename(classname, name, &filename)
+            if let Some(filename) = &raw_filename {
+                compute_pytest_using_filename(classname, name, filename)
             } else if let Some(network) = network {
                 compute_pytest_using_network(classname, name, network)
             } else {
@@ -91,11 +381,52 @@ pub fn compute_name_framework(
             }
         }
         Framework::Vitest => {
-            format!("{} > {}", classname, name)
+            // the classname is the path of the test file, which is the root of the hierarchy
+            let classname = normalize_path(classname);
+            match split_hierarchy(name, Some(framework)) {
+                Some(segments) => render_hierarchy(std::iter::once(&*classname).chain(segments)),
+                None => format!("{} > {}", classname, name),
+            }
         }
         Framework::PHPUnit => {
             format!("{}::{}", classname, name)
         }
+        Framework::CTest => {
+            // CTest repeats the test name as the classname
+            if classname.is_empty() || classname == name {
+                name.to_string()
+            } else {
+                format!("{}::{}", classname, name)
+            }
+        }
+        Framework::RSpec => compute_rspec(classname, name, filename.as_deref()),
+        Framework::Minitest => {
+            if classname.is_empty() {
+                name.to_string()
+            } else {
+                format!("{}#{}", classname, name)
+            }
+        }
+        Framework::Cucumber => {
+            // the classname is the name of the feature, and the name that of the scenario
+            if classname.is_empty() {
+                name.to_string()
+            } else {
+                format!("{}: {}", classname, name)
+            }
+        }
+        Framework::JUnit | Framework::TestNG | Framework::Kotest | Framework::Spock => {
+            compute_jvm(classname, name)
+        }
+        // this is the `Suite/Test.Case/0` form understood by `--gtest_filter`
+        Framework::GoogleTest => {
+            if classname.is_empty() {
+                name.to_string()
+            } else {
+                format!("{}.{}", classname, name)
+            }
+        }
+        Framework::Catch2 => compute_catch2(classname, name),
     }
 }
 
@@ -133,9 +464,61 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_compute_name_with_unix_filename() {
+        // these skip as many classname components as the path has, like they always did
+        assert_eq!(
+            compute_name(
+                "a.b.c",
+                "d",
+                Some(Framework::Pytest),
+                Some("/work/a/b.py"),
+                None
+            ),
+            "/work/a/b.py::d"
+        );
+        assert_eq!(
+            compute_name(
+                "test_x.TestY",
+                "test_z",
+                Some(Framework::Pytest),
+                Some("tests/test_x.py"),
+                None
+            ),
+            "tests/test_x.py::test_z"
+        );
+    }
+
+    #[test]
+    fn test_compute_name_with_windows_filename() {
+        assert_eq!(normalize_path(r"C:\a\b.py"), "/a/b.py");
+        assert_eq!(normalize_path("a/b.py"), "a/b.py");
+
+        for filename in [r"a\b.py", r"A\B.py", r"C:\work\a\b.py", "C:/work/a/b.py"] {
+            assert_eq!(
+                compute_name("a.b.c", "d", Some(Framework::Pytest), Some(filename), None),
+                "a/b.py::c::d"
+            );
+        }
+        assert_eq!(
+            compute_name("b.c", "d", Some(Framework::Pytest), Some(r"src\b.py"), None),
+            "src/b.py::c::d"
+        );
+        assert_eq!(
+            compute_name(
+                r"src\calc.test.ts",
+                "adds",
+                Some(Framework::Vitest),
+                None,
+                None
+            ),
+            "src/calc.test.ts > adds"
+        );
+    }
+
     #[test]
     fn test_compute_name_with_network() {
-        let network = ["a/b/c.py"].iter().map(|e| e.to_string()).collect();
+        let network = Network::new(["a/b/c.py".to_string()], &[]);
         assert_eq!(
             compute_name("a.b.c", "d", Some(Framework::Pytest), None, Some(&network)),
             "a/b/c.py::d"
@@ -144,7 +527,7 @@ mod tests {
 
     #[test]
     fn test_compute_name_with_network_actual_classname() {
-        let network = ["a/b.py"].iter().map(|e| e.to_string()).collect();
+        let network = Network::new(["a/b.py".to_string()], &[]);
         assert_eq!(
             compute_name("a.b.c", "d", Some(Framework::Pytest), None, Some(&network)),
             "a/b.py::c::d"
@@ -153,13 +536,114 @@ mod tests {
 
     #[test]
     fn test_compute_name_with_network_actual_classname_no_match() {
-        let network = ["d.py"].iter().map(|e| e.to_string()).collect();
+        let network = Network::new(["d.py".to_string()], &[]);
         assert_eq!(
             compute_name("a.b.c", "d", Some(Framework::Pytest), None, Some(&network)),
             "a.b.c::d"
         );
     }
 
+    #[test]
+    fn test_compute_name_with_network_src_layout() {
+        let network = Network::new(["src/a/b.py".to_string(), "src/a.py".to_string()], &[]);
+        assert_eq!(
+            compute_name("a.b.C", "d", Some(Framework::Pytest), None, Some(&network)),
+            "src/a/b.py::C::d"
+        );
+    }
+
+    #[test]
+    fn test_split_parameters() {
+        let pytest = Some(Framework::Pytest);
+        assert_eq!(
+            split_parameters("test_x[a-1]", pytest),
+            Some(("test_x", "a-1"))
+        );
+        assert_eq!(
+            split_parameters("test_x[a[0]]", pytest),
+            Some(("test_x", "a[0]"))
+        );
+        assert_eq!(split_parameters("test_x", pytest), None);
+        assert_eq!(split_parameters("test_x[]", pytest), None);
+
+        let junit = Some(Framework::JUnit);
+        assert_eq!(
+            split_parameters("add(int)[2]", junit),
+            Some(("add(int)", "2"))
+        );
+        assert_eq!(split_parameters("[1] a, 1", junit), None);
+        assert_eq!(split_parameters("[1] a[2]", junit), None);
+        assert_eq!(split_display_name("[1] a, 1"), Some(("1", "a, 1")));
+        assert_eq!(split_display_name("[a] 1"), None);
+        assert_eq!(
+            find_test_template(
+                "\nunique-id: [engine:junit-jupiter]/[class:a.B]/[test-template:add(int)]/[test-template-invocation:#2]\ndisplay-name: [2] 1\n"
+            ),
+            Some("add(int)")
+        );
+        assert_eq!(find_test_template("display-name: [2] 1"), None);
+        assert_eq!(
+            split_parameters("adds [a: 1, #0]", Some(Framework::Spock)),
+            Some(("adds", "a: 1, #0"))
+        );
+        assert_eq!(split_parameters("matches[a-z]", junit), None);
+        assert_eq!(split_parameters("test_x[1]", None), None);
+    }
+
+    #[test]
+    fn test_hierarchy() {
+        let jest = Some(Framework::Jest);
+        assert_eq!(
+            split_hierarchy("Title › when rendered › renders", jest),
+            Some(vec!["Title", "when rendered", "renders"])
+        );
+        assert_eq!(
+            split_hierarchy("a > b", Some(Framework::Vitest)),
+            Some(vec!["a", "b"])
+        );
+        assert_eq!(split_hierarchy("a", Some(Framework::Vitest)), None);
+        assert_eq!(split_hierarchy("a > b", Some(Framework::Pytest)), None);
+
+        // the default `jest-junit` templates
+        let name = "Title when rendered renders";
+        assert_eq!(
+            name_hierarchy(name, name, "Title", jest),
+            ["Title", "when rendered renders"]
+        );
+        assert_eq!(name_hierarchy(name, name, "Other", jest), [name]);
+        assert_eq!(
+            name_hierarchy("Title › when rendered", "renders", "Title", jest),
+            ["Title", "when rendered", "renders"]
+        );
+        assert_eq!(
+            name_hierarchy("Title > when rendered > renders", "renders", "", jest),
+            ["Title", "when rendered", "renders"]
+        );
+        assert_eq!(
+            name_hierarchy(
+                "file.test.ts",
+                "a > b",
+                "file.test.ts",
+                Some(Framework::Vitest)
+            ),
+            ["a", "b"]
+        );
+        assert!(name_hierarchy("a", "b", "c", Some(Framework::Pytest)).is_empty());
+
+        assert_eq!(render_hierarchy(["a", "b"]), "a > b");
+        assert_eq!(render_hierarchy(["a > b", r"c\d"]), r"a \> b > c\\d");
+        assert_eq!(
+            compute_name("Title", "Title › x>1 › works", jest, None, None),
+            r"Title > x\>1 > works"
+        );
+        // names without a separator keep their computed names
+        assert_eq!(compute_name("", r"x>1\y", jest, None, None), r"x>1\y");
+        assert_eq!(
+            compute_name("a.test.ts", "x>1", Some(Framework::Vitest), None, None),
+            "a.test.ts > x>1"
+        );
+    }
+
     #[test]
     fn test_compute_name_jest() {
         assert_eq!(
@@ -202,6 +686,178 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_compute_name_ctest() {
+        assert_eq!(
+            compute_name(
+                "a_unit_test",
+                "a_unit_test",
+                Some(Framework::CTest),
+                None,
+                None
+            ),
+            "a_unit_test"
+        );
+        assert_eq!(
+            compute_name("suite", "a_unit_test", Some(Framework::CTest), None, None),
+            "suite::a_unit_test"
+        );
+    }
+
+    #[test]
+    fn test_compute_name_rspec() {
+        assert_eq!(
+            compute_name(
+                "spec.foo_spec",
+                "./spec/foo_spec.rb[1:2:3]",
+                Some(Framework::RSpec),
+                Some("./spec/foo_spec.rb"),
+                None
+            ),
+            "./spec/foo_spec.rb[1:2:3]"
+        );
+        assert_eq!(
+            compute_name(
+                "spec.foo_spec",
+                "Foo does the thing",
+                Some(Framework::RSpec),
+                Some("./spec/foo_spec.rb"),
+                None
+            ),
+            "./spec/foo_spec.rb::Foo does the thing"
+        );
+        assert_eq!(
+            compute_name(
+                "spec.foo_spec",
+                "Foo does the thing",
+                Some(Framework::RSpec),
+                None,
+                None
+            ),
+            "./spec/foo_spec.rb::Foo does the thing"
+        );
+    }
+
+    #[test]
+    fn test_compute_name_minitest() {
+        assert_eq!(
+            compute_name(
+                "UserTest",
+                "test_validates_email",
+                Some(Framework::Minitest),
+                Some("test/models/user_test.rb"),
+                None
+            ),
+            "UserTest#test_validates_email"
+        );
+    }
+
+    #[test]
+    fn test_compute_name_cucumber() {
+        assert_eq!(
+            compute_name(
+                "Addition",
+                "Add two numbers",
+                Some(Framework::Cucumber),
+                None,
+                None
+            ),
+            "Addition: Add two numbers"
+        );
+    }
+
+    #[test]
+    fn test_compute_name_jvm() {
+        assert_eq!(
+            compute_name(
+                "com.example.CalculatorTest$WhenEmpty",
+                "shouldAdd(int, int)[1]",
+                Some(Framework::JUnit),
+                None,
+                None
+            ),
+            "com.example.CalculatorTest.WhenEmpty.shouldAdd(int, int)[1]"
+        );
+        assert_eq!(
+            compute_name(
+                "com.example.CalculatorTest",
+                "[2] 1, 2",
+                Some(Framework::JUnit),
+                None,
+                None
+            ),
+            "com.example.CalculatorTest.[2] 1, 2"
+        );
+        assert_eq!(
+            compute_name(
+                "com.example.CalculatorSpec",
+                "adds numbers [a: 1, b: 2, #0]",
+                Some(Framework::Spock),
+                None,
+                None
+            ),
+            "com.example.CalculatorSpec.adds numbers[0]"
+        );
+        assert_eq!(
+            compute_name(
+                "com.example.CalculatorTest",
+                "testAdd",
+                Some(Framework::TestNG),
+                None,
+                None
+            ),
+            "com.example.CalculatorTest.testAdd"
+        );
+        assert_eq!(
+            compute_name(
+                "com.example.CalculatorTest",
+                "a list [of things]",
+                Some(Framework::Kotest),
+                None,
+                None
+            ),
+            "com.example.CalculatorTest.a list [of things]"
+        );
+    }
+
+    #[test]
+    fn test_compute_name_googletest() {
+        assert_eq!(
+            compute_name(
+                "Instantiation/ParamTest",
+                "Works/0",
+                Some(Framework::GoogleTest),
+                None,
+                None
+            ),
+            "Instantiation/ParamTest.Works/0"
+        );
+    }
+
+    #[test]
+    fn test_compute_name_catch2() {
+        assert_eq!(
+            compute_name(
+                "tests.global",
+                "vectors can be sized/resizing",
+                Some(Framework::Catch2),
+                None,
+                None
+            ),
+            "vectors can be sized/resizing"
+        );
+        assert_eq!(
+            compute_name(
+                "tests.Fixture",
+                "uses the fixture",
+                Some(Framework::Catch2),
+                None,
+                None
+            ),
+            "Fixture::uses the fixture"
+        );
+    }
+
     #[test]
     fn test_compute_name_none() {
         assert_eq!(compute_name("a.b.c", "d", None, None, None), "a.b.c::d");
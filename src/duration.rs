@@ -0,0 +1,182 @@
+use thiserror::Error;
+
+/// A duration in seconds, parsed from the `time` attribute of a testcase or testsuite.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParsedDuration {
+    pub seconds: f64,
+    /// Whether the value was ambiguous, and `seconds` is only our best guess.
+    pub ambiguous: bool,
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum DurationError {
+    #[error("Invalid duration `{0}`")]
+    Invalid(String),
+    #[error("Negative duration `{0}`")]
+    Negative(String),
+    #[error("Duration `{0}` is not a finite number")]
+    NotFinite(String),
+}
+
+/// The units a duration can be suffixed with, and their length in seconds.
+static UNITS: [(&str, f64); 12] = [
+    ("ns", 1e-9),
+    ("us", 1e-6),
+    ("µs", 1e-6),
+    ("ms", 1e-3),
+    ("minutes", 60.),
+    ("seconds", 1.),
+    ("secs", 1.),
+    ("sec", 1.),
+    ("s", 1.),
+    ("min", 60.),
+    ("m", 60.),
+    ("h", 3600.),
+];
+
+/// Parses a number which may use `,` or `.` as decimal or thousands separators.
+///
+/// Returns the number, and whether the meaning of the separators had to be guessed.
+fn parse_number(value: &str) -> Option<(f64, bool)> {
+    let (mut number, ambiguous) = match (value.rfind('.'), value.rfind(',')) {
+        // `1,234.5` or `1.234,5`, the last separator is the decimal one
+        (Some(dot), Some(comma)) if dot > comma => (value.replace(',', ""), false),
+        (Some(_), Some(_)) => (value.replace('.', "").replace(',', "."), false),
+        // `1.234.567` can only use thousands separators
+        (Some(_), None) if value.matches('.').count() > 1 => (value.replace('.', ""), false),
+        (Some(_), None) | (None, None) => (value.to_string(), false),
+        (None, Some(_)) if value.matches(',').count() > 1 => (value.replace(',', ""), false),
+        (None, Some(comma)) => {
+            // `0,123` or `1,5` use a decimal comma, but `1,234` could also be
+            // a thousands separator, in which case we still guess it is a decimal one
+            let ambiguous = value[comma + 1..].len() == 3 && !value.starts_with(['0', '-']);
+            (value.replace(',', "."), ambiguous)
+        }
+    };
+    if number.starts_with('.') {
+        number.insert(0, '0');
+    }
+
+    let is_numeric = number
+        .chars()
+        .all(|c| c.is_ascii_digit() || matches!(c, '.' | '-' | '+' | 'e' | 'E'));
+    if !is_numeric {
+        return None;
+    }
+    number.parse().ok().map(|number| (number, ambiguous))
+}
+
+/// Parses an ISO 8601 duration like `PT1M30.5S`, only supporting the time components.
+fn parse_iso8601(value: &str) -> Option<f64> {
+    let mut rest = value.strip_prefix("PT").filter(|rest| !rest.is_empty())?;
+    let mut seconds = 0.;
+    for (designator, factor) in [('H', 3600.), ('M', 60.), ('S', 1.)] {
+        if let Some((number, remainder)) = rest.split_once(designator) {
+            seconds += number.replace(',', ".").parse::<f64>().ok()? * factor;
+            rest = remainder;
+        }
+    }
+    rest.is_empty().then_some(seconds)
+}
+
+/// Parses a duration into seconds.
+///
+/// Besides plain seconds, this understands numbers with thousands separators or a
+/// decimal comma, values with a unit like `12ms`, and ISO 8601 durations like `PT1.5S`.
+pub fn parse_duration(value: &str) -> Result<ParsedDuration, DurationError> {
+    let trimmed = value.trim();
+
+    let parsed = match parse_iso8601(trimmed) {
+        Some(seconds) => Some((seconds, false)),
+        None => {
+            let (number, factor) = UNITS
+                .iter()
+                .find_map(|(unit, factor)| Some((trimmed.strip_suffix(unit)?, *factor)))
+                .unwrap_or((trimmed, 1.));
+            parse_number(number.trim_end()).map(|(number, ambiguous)| (number * factor, ambiguous))
+        }
+    };
+
+    let Some((seconds, ambiguous)) = parsed else {
+        return Err(DurationError::Invalid(value.to_string()));
+    };
+    if !seconds.is_finite() {
+        return Err(DurationError::NotFinite(value.to_string()));
+    }
+    if seconds < 0. {
+        return Err(DurationError::Negative(value.to_string()));
+    }
+
+    Ok(ParsedDuration { seconds, ambiguous })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seconds(value: &str) -> f64 {
+        let parsed = parse_duration(value).unwrap();
+        assert!(!parsed.ambiguous, "{value} should not be ambiguous");
+        parsed.seconds
+    }
+
+    #[test]
+    fn test_parse_plain() {
+        assert_eq!(seconds("0.123"), 0.123);
+        assert_eq!(seconds(" 12 "), 12.);
+        assert_eq!(seconds(".5"), 0.5);
+        assert_eq!(seconds("1e-3"), 0.001);
+    }
+
+    #[test]
+    fn test_parse_separators() {
+        assert_eq!(seconds("1,234.5"), 1234.5);
+        assert_eq!(seconds("1.234,5"), 1234.5);
+        assert_eq!(seconds("1,234,567"), 1234567.);
+        assert_eq!(seconds("1.234.567"), 1234567.);
+        assert_eq!(seconds("0,123"), 0.123);
+        assert_eq!(seconds("1,5"), 1.5);
+
+        let parsed = parse_duration("1,234").unwrap();
+        assert_eq!(parsed.seconds, 1.234);
+        assert!(parsed.ambiguous);
+    }
+
+    #[test]
+    fn test_parse_units() {
+        assert_eq!(seconds("12ms"), 0.012);
+        assert_eq!(seconds("1.5 s"), 1.5);
+        assert_eq!(seconds("2min"), 120.);
+        assert_eq!(seconds("2 minutes"), 120.);
+        assert_eq!(seconds("1h"), 3600.);
+        assert_eq!(seconds("500us"), 0.0005);
+    }
+
+    #[test]
+    fn test_parse_iso8601() {
+        assert_eq!(seconds("PT1.5S"), 1.5);
+        assert_eq!(seconds("PT1M30S"), 90.);
+        assert_eq!(seconds("PT1H"), 3600.);
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert_eq!(
+            parse_duration("abc"),
+            Err(DurationError::Invalid("abc".into()))
+        );
+        assert_eq!(parse_duration(""), Err(DurationError::Invalid("".into())));
+        assert_eq!(
+            parse_duration("-1.5"),
+            Err(DurationError::Negative("-1.5".into()))
+        );
+        assert_eq!(
+            parse_duration("NaN"),
+            Err(DurationError::Invalid("NaN".into()))
+        );
+        assert_eq!(
+            parse_duration("1e999"),
+            Err(DurationError::NotFinite("1e999".into()))
+        );
+    }
+}
@@ -0,0 +1,120 @@
+use std::borrow::Cow;
+use std::ops::Range;
+
+use encoding_rs::{Encoding, UTF_8};
+
+/// Finds the position of the `encoding` declared in the XML prolog, if any.
+fn declared_encoding_range(data: &[u8]) -> Option<Range<usize>> {
+    let prolog = data.strip_prefix(b"<?xml")?;
+    let end = prolog.windows(2).position(|window| window == b"?>")?;
+    let prolog = &prolog[..end];
+
+    let start = prolog.windows(8).position(|window| window == b"encoding")?;
+    let value = prolog[start + 8..].trim_ascii_start().strip_prefix(b"=")?;
+    let value = value.trim_ascii_start();
+    let (quote, value) = value.split_first()?;
+    if !matches!(quote, b'"' | b'\'') {
+        return None;
+    }
+    let end = value.iter().position(|c| c == quote)?;
+
+    // the `value` is the rest of the `prolog`, which starts after `<?xml`
+    let start = b"<?xml".len() + prolog.len() - value.len();
+    Some(start..start + end)
+}
+
+/// Finds the `encoding` declared in the XML prolog, if any.
+fn declared_encoding(data: &[u8]) -> Option<&'static Encoding> {
+    Encoding::for_label(&data[declared_encoding_range(data)?])
+}
+
+/// Transcodes a test results file to UTF-8.
+///
+/// The encoding is detected from a byte order mark, or from the `encoding` declared
+/// in the XML prolog, falling back to UTF-8.
+///
+/// The `encoding` declared in the prolog is rewritten to `UTF-8`, so that the
+/// transcoded file can be read again.
+///
+/// Byte sequences which are invalid in that encoding are replaced with U+FFFD.
+/// If that happens, the name of the encoding is returned alongside the data.
+pub fn transcode_to_utf8(data: &[u8]) -> (Cow<'_, [u8]>, Option<&'static str>) {
+    let (encoding, bom_length) = match Encoding::for_bom(data) {
+        Some((encoding, bom_length)) => (encoding, bom_length),
+        // a prolog that could be read is never in UTF-16, no matter what it declares
+        None => match declared_encoding(data).filter(|encoding| encoding.is_ascii_compatible()) {
+            Some(encoding) => (encoding, 0),
+            None => (UTF_8, 0),
+        },
+    };
+
+    let (decoded, had_errors) = encoding.decode_without_bom_handling(&data[bom_length..]);
+    let mut decoded = match decoded {
+        Cow::Borrowed(decoded) => Cow::Borrowed(decoded.as_bytes()),
+        Cow::Owned(decoded) => Cow::Owned(decoded.into_bytes()),
+    };
+
+    if let Some(range) = declared_encoding_range(&decoded) {
+        if Encoding::for_label(&decoded[range.clone()]) != Some(UTF_8) {
+            decoded.to_mut().splice(range, UTF_8.name().bytes());
+        }
+    }
+
+    (decoded, had_errors.then_some(encoding.name()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_utf8() {
+        let data = "<?xml version=\"1.0\"?><testcase name=\"ä\" />".as_bytes();
+        let (transcoded, invalid) = transcode_to_utf8(data);
+        assert!(matches!(transcoded, Cow::Borrowed(_)));
+        assert_eq!(transcoded, data);
+        assert_eq!(invalid, None);
+
+        let mut with_bom = b"\xEF\xBB\xBF".to_vec();
+        with_bom.extend_from_slice(data);
+        let (transcoded, invalid) = transcode_to_utf8(&with_bom);
+        assert_eq!(transcoded, data);
+        assert_eq!(invalid, None);
+    }
+
+    #[test]
+    fn test_declared_encoding() {
+        let data = b"<?xml version='1.0' encoding = 'ISO-8859-1' ?><testcase name=\"\xE4\" />";
+        let (transcoded, invalid) = transcode_to_utf8(data);
+        assert_eq!(
+            std::str::from_utf8(&transcoded).unwrap(),
+            "<?xml version='1.0' encoding = 'UTF-8' ?><testcase name=\"ä\" />"
+        );
+        assert_eq!(invalid, None);
+    }
+
+    #[test]
+    fn test_utf16_bom() {
+        let xml = "<?xml version=\"1.0\" encoding=\"UTF-16\"?><testcase name=\"ä\" />";
+        let mut data = b"\xFF\xFE".to_vec();
+        data.extend(xml.encode_utf16().flat_map(u16::to_le_bytes));
+
+        let (transcoded, invalid) = transcode_to_utf8(&data);
+        assert_eq!(
+            std::str::from_utf8(&transcoded).unwrap(),
+            xml.replace("UTF-16", "UTF-8")
+        );
+        assert_eq!(invalid, None);
+    }
+
+    #[test]
+    fn test_invalid_sequence() {
+        let data = b"<testcase name=\"a\xFFb\" />";
+        let (transcoded, invalid) = transcode_to_utf8(data);
+        assert_eq!(
+            std::str::from_utf8(&transcoded).unwrap(),
+            "<testcase name=\"a\u{FFFD}b\" />"
+        );
+        assert_eq!(invalid, Some("UTF-8"));
+    }
+}
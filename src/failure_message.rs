@@ -4,6 +4,8 @@ use pyo3::prelude::*;
 use regex::Regex;
 use rinja::Template;
 
+use crate::testrun::TestFailure;
+
 #[pyfunction]
 /// Escapes characters that will break Markdown Templating.
 pub fn escape_message(failure_message: &str) -> String {
@@ -64,6 +66,10 @@ pub struct Failure {
     failure_message: Option<String>,
     duration: f64,
     build_url: Option<String>,
+    /// All the failures of the testrun, each rendered as its own stack trace.
+    /// The `failure_message` is used when this is empty.
+    #[pyo3(default)]
+    failures: Vec<TestFailure>,
 }
 #[derive(FromPyObject, Debug)]
 pub struct MessagePayload {
@@ -85,9 +91,22 @@ struct TemplateContext {
 struct TemplateFailure {
     test_name: String,
     duration: String,
-    backticks: String,
     build_url: Option<String>,
-    stack_trace: Vec<String>,
+    stack_traces: Vec<TemplateStackTrace>,
+}
+struct TemplateStackTrace {
+    backticks: String,
+    lines: Vec<String>,
+}
+
+fn stack_trace(failure_message: Option<&str>) -> TemplateStackTrace {
+    let failure_message = failure_message.unwrap_or("No failure message available");
+    let lines = failure_message.split('\n').map(escape_message).collect();
+
+    let num_backticks: usize = max(longest_repeated_substring(failure_message, '`') + 1, 3);
+    let backticks = "`".repeat(num_backticks);
+
+    TemplateStackTrace { backticks, lines }
 }
 
 #[pyfunction]
@@ -106,22 +125,21 @@ pub fn build_message(mut payload: MessagePayload) -> String {
         .into_iter()
         .take(3)
         .map(|failure| {
-            let failure_message = failure
-                .failure_message
-                .as_deref()
-                .unwrap_or("No failure message available");
-            let stack_trace: Vec<String> =
-                failure_message.split('\n').map(escape_message).collect();
-
-            let num_backticks: usize = max(longest_repeated_substring(failure_message, '`') + 1, 3);
-            let backticks = "`".repeat(num_backticks);
+            let stack_traces = if failure.failures.is_empty() {
+                vec![stack_trace(failure.failure_message.as_deref())]
+            } else {
+                failure
+                    .failures
+                    .iter()
+                    .map(|f| stack_trace(f.details.as_deref().or(f.message.as_deref())))
+                    .collect()
+            };
 
             TemplateFailure {
                 test_name: failure.name,
                 duration: format!("{:.3}", failure.duration),
-                backticks,
                 build_url: failure.build_url,
-                stack_trace,
+                stack_traces,
             }
         })
         .collect();
@@ -1,23 +1,29 @@
 use anyhow::{Context, Result};
 use pyo3::prelude::*;
 use serde_json::Value;
-use std::collections::HashSet;
-use std::fmt;
 
 use quick_xml::events::attributes::{Attribute, Attributes};
 use quick_xml::events::{BytesStart, Event};
 use quick_xml::reader::Reader;
 
-use crate::compute_name::{compute_name, unescape_str};
-use crate::testrun::{check_testsuites_name, Framework, Outcome, PropertiesValue, Testrun};
+use crate::compute_name::{
+    compute_name, find_test_template, name_hierarchy, normalize_path, split_display_name,
+    split_parameters, unescape_str,
+};
+use crate::duration::parse_duration;
+use crate::network::Network;
+use crate::options::ParseOptions;
+use crate::testrun::{
+    check_testsuites_name, Framework, Outcome, PropertiesValue, TestFailure, Testrun,
+};
 use crate::validated_string::ValidatedString;
 use crate::warning::WarningInfo;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 enum ParseAttrsError {
-    #[error("Limit of string is 1000 chars, for {0}, we got {1}")]
-    AttrTooLong(&'static str, usize),
+    #[error("Limit of string is {2} chars, for {0}, we got {1}")]
+    AttrTooLong(&'static str, usize, usize),
     #[error("Error converting attribute {0} to UTF-8 string")]
     ConversionError(&'static str),
     #[error("Missing name attribute in testcase")]
@@ -34,12 +40,25 @@ fn convert_attribute(attribute: Attribute) -> Result<String> {
 fn extract_validated_string(
     attribute: Attribute,
     field_name: &'static str,
+    max_length: usize,
+    options: &ParseOptions,
 ) -> Result<ValidatedString, ParseAttrsError> {
     let unvalidated_string =
         convert_attribute(attribute).map_err(|_| ParseAttrsError::ConversionError(field_name))?;
-    let string_len = unvalidated_string.len();
-    ValidatedString::from_string(unvalidated_string)
-        .map_err(|_| ParseAttrsError::AttrTooLong(field_name, string_len))
+    limit_field(unvalidated_string, field_name, max_length, options)
+}
+
+/// Checks the length of a field of a testcase, like its attributes or computed name.
+fn limit_field(
+    value: String,
+    field_name: &'static str,
+    max_length: usize,
+    options: &ParseOptions,
+) -> Result<ValidatedString, ParseAttrsError> {
+    let string_len = value.len();
+    options
+        .limit_length(value, max_length)
+        .map_err(|_| ParseAttrsError::AttrTooLong(field_name, string_len, max_length))
 }
 
 struct TestcaseAttrs {
@@ -47,14 +66,22 @@ struct TestcaseAttrs {
     time: Option<String>,
     classname: Option<ValidatedString>,
     file: Option<ValidatedString>,
+    status: Option<String>,
+    result: Option<String>,
 }
 
 // originally from https://gist.github.com/scott-codecov/311c174ecc7de87f7d7c50371c6ef927#file-cobertura-rs-L18-L31
-fn parse_testcase_attrs(attributes: Attributes) -> Result<TestcaseAttrs, ParseAttrsError> {
+fn parse_testcase_attrs(
+    attributes: Attributes,
+    options: &ParseOptions,
+) -> Result<TestcaseAttrs, ParseAttrsError> {
+    let limits = &options.field_limits;
     let mut name: Option<ValidatedString> = None;
     let mut time: Option<String> = None;
     let mut classname: Option<ValidatedString> = None;
     let mut file: Option<ValidatedString> = None;
+    let mut status: Option<String> = None;
+    let mut result: Option<String> = None;
 
     for attribute in attributes {
         let attribute = attribute.map_err(|_| ParseAttrsError::ParseError)?;
@@ -67,13 +94,40 @@ fn parse_testcase_attrs(attributes: Attributes) -> Result<TestcaseAttrs, ParseAt
                 );
             }
             b"classname" => {
-                classname = Some(extract_validated_string(attribute, "classname")?);
+                classname = Some(extract_validated_string(
+                    attribute,
+                    "classname",
+                    limits.classname,
+                    options,
+                )?);
             }
             b"name" => {
-                name = Some(extract_validated_string(attribute, "name")?);
+                name = Some(extract_validated_string(
+                    attribute,
+                    "name",
+                    limits.name,
+                    options,
+                )?);
             }
             b"file" => {
-                file = Some(extract_validated_string(attribute, "file")?);
+                file = Some(extract_validated_string(
+                    attribute,
+                    "file",
+                    limits.file,
+                    options,
+                )?);
+            }
+            b"status" => {
+                status = Some(
+                    convert_attribute(attribute)
+                        .map_err(|_| ParseAttrsError::ConversionError("status"))?,
+                );
+            }
+            b"result" => {
+                result = Some(
+                    convert_attribute(attribute)
+                        .map_err(|_| ParseAttrsError::ConversionError("result"))?,
+                );
             }
             _ => {}
         }
@@ -85,11 +139,48 @@ fn parse_testcase_attrs(attributes: Attributes) -> Result<TestcaseAttrs, ParseAt
             time,
             classname,
             file,
+            status,
+            result,
         }),
         None => Err(ParseAttrsError::NameMissing),
     }
 }
 
+/// Maps the `status` attribute that CTest writes on `testcase` elements onto an [`Outcome`].
+///
+/// CTest does not necessarily write a `failure` element for failed tests.
+fn ctest_status_outcome(status: &str) -> Option<Outcome> {
+    match status {
+        "run" => Some(Outcome::Pass),
+        "fail" => Some(Outcome::Failure),
+        "notrun" | "disabled" => Some(Outcome::Skip),
+        _ => None,
+    }
+}
+
+/// Maps the `result` attribute that GoogleTest writes on `testcase` elements onto an [`Outcome`].
+///
+/// GoogleTest also writes a `status` attribute, which is `run` even for skipped tests.
+fn googletest_result_outcome(result: &str) -> Option<Outcome> {
+    match result {
+        "completed" => Some(Outcome::Pass),
+        "skipped" | "suppressed" => Some(Outcome::Skip),
+        _ => None,
+    }
+}
+
+/// Extracts the file from a GoogleTest failure message, which starts with the
+/// location of the failed assertion, like `math_test.cc:7` or `math_test.cc(7)` on Windows.
+fn googletest_failure_file(message: &str) -> Option<&str> {
+    let location = message.lines().next()?.trim_end_matches(':');
+    let (file, line) = match location.strip_suffix(')') {
+        Some(location) => location.rsplit_once('(')?,
+        None => location.rsplit_once(':')?,
+    };
+    let is_line = !line.is_empty() && line.chars().all(|c| c.is_ascii_digit());
+    (is_line && !file.is_empty()).then_some(file)
+}
+
 fn get_attribute(e: &BytesStart, name: &str) -> Result<Option<String>> {
     let attr = if let Some(message) = e
         .try_get_attribute(name)
@@ -102,47 +193,209 @@ fn get_attribute(e: &BytesStart, name: &str) -> Result<Option<String>> {
     Ok(attr)
 }
 
+/// Parses the `time` attribute of a testcase, warning about durations which
+/// are invalid or ambiguous.
+fn parse_testcase_duration(
+    time: Option<&str>,
+    location: u64,
+    warnings: &mut Vec<WarningInfo>,
+) -> Option<f64> {
+    let time = time.filter(|time| !time.trim().is_empty())?;
+    match parse_duration(time) {
+        Ok(parsed) => {
+            if parsed.ambiguous {
+                warnings.push(WarningInfo::new(
+                    format!(
+                        "Duration `{}` is ambiguous, interpreting it as {} seconds",
+                        time, parsed.seconds
+                    ),
+                    location,
+                ));
+            }
+            Some(parsed.seconds)
+        }
+        Err(error) => {
+            warnings.push(WarningInfo::new(
+                format!("{}, ignoring it", error),
+                location,
+            ));
+            None
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 fn populate(
     rel_attrs: TestcaseAttrs,
     testsuite: ValidatedString,
-    testsuite_time: Option<&str>,
+    duration: Option<f64>,
     framework: Option<Framework>,
-    network: Option<&HashSet<String>>,
-) -> Result<(Testrun, Option<Framework>)> {
+    network: Option<&Network>,
+    options: &ParseOptions,
+    location: u64,
+    warnings: &mut Vec<WarningInfo>,
+) -> Result<Testrun, ParseAttrsError> {
     let name = rel_attrs.name;
     let classname = rel_attrs.classname.unwrap_or_default();
-    let duration = rel_attrs
-        .time
-        .as_deref()
-        .or(testsuite_time)
-        .and_then(|t| t.parse().ok());
+    // the file is normalized once all the computed names are final, see `normalize_filenames`
     let file = rel_attrs.file;
+    let status_outcome = rel_attrs.status.as_deref().and_then(ctest_status_outcome);
+    let result_outcome = rel_attrs
+        .result
+        .as_deref()
+        .and_then(googletest_result_outcome);
 
     let mut t = Testrun {
         name,
         classname,
         duration,
-        outcome: Outcome::Pass,
+        outcome: result_outcome.or(status_outcome).unwrap_or(Outcome::Pass),
         testsuite,
-        failure_message: None,
         filename: file,
-        build_url: None,
-        computed_name: ValidatedString::default(),
-        properties: PropertiesValue(None),
+        ..Default::default()
     };
 
-    let framework = framework.or_else(|| t.framework());
-    let computed_name = compute_name(
-        &t.classname,
-        &t.name,
+    let framework = framework
+        .or_else(|| t.detect_framework())
+        .or_else(|| result_outcome.map(|_| Framework::GoogleTest));
+    set_computed_name(&mut t, framework, network, options, location, warnings)?;
+
+    Ok(t)
+}
+
+fn set_computed_name(
+    t: &mut Testrun,
+    framework: Option<Framework>,
+    network: Option<&Network>,
+    options: &ParseOptions,
+    location: u64,
+    warnings: &mut Vec<WarningInfo>,
+) -> Result<(), ParseAttrsError> {
+    t.framework = framework;
+    if let (Some(Framework::Pytest), None, Some(network), None) = (
         framework,
-        t.filename.as_deref(),
+        &t.filename,
         network,
+        options.naming_rules.template_for(framework),
+    ) {
+        warn_ambiguous_network(&unescape_str(&t.classname), network, location, warnings);
+    }
+
+    let computed_name;
+    let parameterized;
+    match junit5_invocation(t, framework) {
+        Some((method, index, arguments)) => {
+            let name = format!("{}[{}]", method, index);
+            computed_name = render_computed_name(t, &name, network, options);
+            parameterized = Some((
+                render_computed_name(t, method, network, options),
+                unescape_str(arguments).into_owned(),
+            ));
+        }
+        None => {
+            computed_name = render_computed_name(t, &t.name, network, options);
+            parameterized = split_parameters(&t.name, framework).map(|(base, parameters)| {
+                (
+                    render_computed_name(t, base, network, options),
+                    unescape_str(parameters).into_owned(),
+                )
+            });
+        }
+    }
+
+    t.computed_name = limit_field(
+        computed_name,
+        "computed_name",
+        options.field_limits.computed_name,
+        options,
+    )?;
+    (t.base_computed_name, t.parameters) = match parameterized {
+        Some((base_computed_name, parameters)) => (
+            Some(limit_field(
+                base_computed_name,
+                "base_computed_name",
+                options.field_limits.computed_name,
+                options,
+            )?),
+            Some(parameters),
+        ),
+        None => (None, None),
+    };
+    let (classname, name, testsuite) = (
+        unescape_str(&t.classname),
+        unescape_str(&t.name),
+        unescape_str(&t.testsuite),
     );
-    t.computed_name = ValidatedString::from_string(computed_name)
-        .context("Error converting computed name to ValidatedString")?;
+    t.hierarchy = name_hierarchy(&classname, &name, &testsuite, framework)
+        .into_iter()
+        .map(String::from)
+        .collect();
+    Ok(())
+}
 
-    Ok((t, framework))
+/// Returns the test method, invocation index and arguments of a JUnit 5 invocation
+/// named by its display name, like `[2] 1, 2`, once its output revealed the method.
+fn junit5_invocation(t: &Testrun, framework: Option<Framework>) -> Option<(&str, &str, &str)> {
+    if !matches!(
+        framework?,
+        Framework::JUnit | Framework::TestNG | Framework::Kotest | Framework::Spock
+    ) {
+        return None;
+    }
+    let (index, arguments) = split_display_name(&t.name)?;
+    let method = find_test_template(t.system_out.as_deref()?)?;
+    Some((method, index, arguments))
+}
+
+/// Computes the name of the testcase `t`, using `name` in place of its own name.
+fn render_computed_name(
+    t: &Testrun,
+    name: &str,
+    network: Option<&Network>,
+    options: &ParseOptions,
+) -> String {
+    let naming_rules = &options.naming_rules;
+    let computed_name = match naming_rules.template_for(t.framework) {
+        Some(template) => template.render(
+            &unescape_str(&t.classname),
+            &unescape_str(name),
+            t.filename.as_deref().map(unescape_str).as_deref(),
+            &unescape_str(&t.testsuite),
+        ),
+        None => compute_name(
+            &t.classname,
+            name,
+            t.framework,
+            t.filename.as_deref(),
+            network,
+        ),
+    };
+    naming_rules.rewrite(computed_name)
+}
+
+/// Warns when the module of a pytest `classname` matches several files of the `network`,
+/// unless the same warning was already given.
+fn warn_ambiguous_network(
+    classname: &str,
+    network: &Network,
+    location: u64,
+    warnings: &mut Vec<WarningInfo>,
+) {
+    let Some(network_match) = network.resolve(classname) else {
+        return;
+    };
+    if network_match.ambiguous.is_empty() {
+        return;
+    }
+    let message = format!(
+        "Classname `{}` matches several files of the network, chose `{}` over `{}`",
+        classname,
+        network_match.path,
+        network_match.ambiguous.join("`, `")
+    );
+    if !warnings.iter().any(|warning| warning.message == message) {
+        warnings.push(WarningInfo::new(message, location));
+    }
 }
 
 pub fn get_position_info(input: &[u8], byte_offset: usize) -> (usize, usize) {
@@ -161,19 +414,76 @@ pub fn get_position_info(input: &[u8], byte_offset: usize) -> (usize, usize) {
     (line, column)
 }
 
-#[derive(Error, Debug)]
-struct NotEvalsPropertyError;
+/// The key of the object collecting the properties which are not `evals`, so that
+/// their names do not collide with the hierarchy of the `evals` properties.
+const PLAIN_PROPERTIES_KEY: &str = "properties";
+
+/// Parses a `property` element, and adds it to `existing_properties`.
+///
+/// Properties are collected into a JSON object under the `properties` key, keyed by
+/// their `name` attribute. A property that is repeated is collected into a list of
+/// all its values. The value is taken from the `value` attribute, or the `body` of
+/// the element if it has none.
+///
+/// Properties starting with `evals` are handled specially, as their `name` encodes
+/// the hierarchy of the `value` attribute, see [`insert_evals_property`].
+fn parse_property_element(
+    e: &BytesStart,
+    body: Option<String>,
+    existing_properties: &mut PropertiesValue,
+    options: &ParseOptions,
+) -> Result<()> {
+    let name = get_attribute(e, "name")?
+        .ok_or_else(|| anyhow::anyhow!("Property must have name attribute"))?;
+    if !options.is_property_allowed(&name) {
+        return Ok(());
+    }
 
-impl fmt::Display for NotEvalsPropertyError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "not evals property")
+    let value = match get_attribute(e, "value")? {
+        Some(value) => value,
+        None => body.ok_or_else(|| anyhow::anyhow!("Property must have value attribute"))?,
+    };
+
+    let properties = existing_properties
+        .0
+        .get_or_insert_with(|| serde_json::json!({}));
+
+    if name.starts_with("evals") {
+        return insert_evals_property(properties, &name, value);
     }
+
+    let Value::Object(map) = properties else {
+        anyhow::bail!("Cannot set value in non-object properties");
+    };
+    let Value::Object(map) = map
+        .entry(PLAIN_PROPERTIES_KEY)
+        .or_insert_with(|| serde_json::json!({}))
+    else {
+        anyhow::bail!("Cannot set value in non-object properties");
+    };
+
+    // CTest joins the labels of a test with `;`
+    if name == "cmake_labels" {
+        let labels = value.split(';').filter(|label| !label.is_empty());
+        map.insert(name, labels.map(Value::from).collect());
+        return Ok(());
+    }
+
+    match map.get_mut(&name) {
+        Some(Value::Array(values)) => values.push(Value::String(value)),
+        Some(existing) => {
+            let first = existing.take();
+            *existing = Value::Array(vec![first, Value::String(value)]);
+        }
+        None => {
+            map.insert(name, Value::String(value));
+        }
+    }
+
+    Ok(())
 }
 
-/// Parses the `property` element found in the `testcase` element.
-///
-/// This function is used to parse the `evals` attribute of the `testcase` element.
-/// It will update the `properties` field of the `testrun` object with the new value.
+/// Inserts an `evals` property into `properties`.
 ///
 /// The `name` attribute in `property` encodes the hierarchy of the `value` attribute
 /// inside `Testrun.properties` (which is a JSON object).
@@ -194,26 +504,13 @@ impl fmt::Display for NotEvalsPropertyError {
 ///         }
 ///     }
 /// }
-fn parse_property_element(e: &BytesStart, existing_properties: &mut PropertiesValue) -> Result<()> {
-    // Early return if not an evals property
-    let name = get_attribute(e, "name")?
-        .filter(|n| n.starts_with("evals"))
-        .ok_or(NotEvalsPropertyError)?;
-
-    let value = get_attribute(e, "value")?
-        .ok_or_else(|| anyhow::anyhow!("Property must have value attribute"))?;
-
+fn insert_evals_property(properties: &mut Value, name: &str, value: String) -> Result<()> {
     let name_parts: Vec<&str> = name.split(".").collect();
     if name_parts.len() < 2 {
         anyhow::bail!("Property name must have at least 2 parts");
     }
 
-    // Initialize properties if needed
-    if existing_properties.0.is_none() {
-        *existing_properties = PropertiesValue(Some(serde_json::json!({})));
-    }
-
-    let mut current = existing_properties.0.as_mut().unwrap();
+    let mut current = properties;
 
     // Navigate through intermediate parts (skip first "evals" and last key)
     for part in &name_parts[1..name_parts.len() - 1] {
@@ -255,290 +552,1104 @@ fn parse_property_element(e: &BytesStart, existing_properties: &mut PropertiesVa
     Ok(())
 }
 
+/// Merges the `inherited` properties into `properties`, without overriding any
+/// of the existing values.
+fn inherit_properties(properties: &mut PropertiesValue, inherited: &PropertiesValue) {
+    fn merge(target: &mut Value, source: &Value) {
+        if let (Value::Object(target), Value::Object(source)) = (target, source) {
+            for (key, value) in source {
+                match target.get_mut(key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        target.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    match (&mut properties.0, &inherited.0) {
+        (_, None) => {}
+        (None, Some(inherited)) => properties.0 = Some(inherited.clone()),
+        (Some(properties), Some(inherited)) => merge(properties, inherited),
+    }
+}
+
 enum TestrunOrSkipped {
-    Testrun(Testrun),
+    Testrun(Box<Testrun>),
     Skipped,
 }
 
+/// Extracts the value of a `property` element from the raw text of its body, which
+/// JUnit reporters write there for multi-line values.
+fn property_body(raw: &str) -> String {
+    let raw = raw.trim();
+    match raw
+        .strip_prefix("<![CDATA[")
+        .and_then(|cdata| cdata.strip_suffix("]]>"))
+    {
+        Some(cdata) => cdata.to_string(),
+        None => unescape_str(raw).into_owned(),
+    }
+}
+
 fn handle_property_element(
     e: &BytesStart,
+    body: Option<String>,
     saved_testrun: &mut Option<TestrunOrSkipped>,
+    suites: &mut [SuiteContext],
+    options: &ParseOptions,
     buffer_position: u64,
     warnings: &mut Vec<WarningInfo>,
 ) -> Result<()> {
-    // Check if there is a testrun currently being processed
-    if saved_testrun.is_none() {
-        return Ok(());
+    // Properties outside of a testcase belong to the innermost testsuite
+    let properties = match saved_testrun {
+        Some(TestrunOrSkipped::Testrun(testrun)) => &mut testrun.properties,
+        Some(TestrunOrSkipped::Skipped) => return Ok(()),
+        None => match suites.last_mut() {
+            Some(suite) => &mut suite.properties,
+            None => return Ok(()),
+        },
+    };
+    if let Err(e) = parse_property_element(e, body, properties, options) {
+        warnings.push(WarningInfo::new(
+            format!("Error parsing `property` element: {}", e),
+            buffer_position,
+        ));
     }
-    let saved = saved_testrun
-        .as_mut()
-        .context("Error accessing saved testrun")?;
-    if let TestrunOrSkipped::Testrun(testrun) = saved {
-        if let Err(e) = parse_property_element(e, &mut testrun.properties) {
-            if !e.is::<NotEvalsPropertyError>() {
-                warnings.push(WarningInfo::new(
-                    format!("Error parsing `property` element: {}", e),
-                    buffer_position,
-                ));
+    Ok(())
+}
+
+/// Reads the `message` and `type` attributes of a `failure`, `error` or rerun element.
+fn parse_failure(outcome: Outcome, e: &BytesStart) -> Result<TestFailure> {
+    Ok(TestFailure {
+        outcome,
+        message: get_attribute(e, "message")?.map(|message| unescape_str(&message).into()),
+        failure_type: get_attribute(e, "type")?
+            .map(|failure_type| unescape_str(&failure_type).into()),
+        details: None,
+    })
+}
+
+/// Marks the testrun as failed, and records the `failure` or `error` element.
+///
+/// An error takes precedence over a failure when a testcase has both.
+fn add_failure(testrun: &mut Testrun, outcome: Outcome, e: &BytesStart) -> Result<()> {
+    if testrun.outcome != Outcome::Error {
+        testrun.outcome = outcome;
+    }
+    testrun.failures.push(parse_failure(outcome, e)?);
+
+    Ok(())
+}
+
+/// Fills in the failure message, summary, type and details from all the failures
+/// recorded for the testrun.
+///
+/// The failure message of each failure is its body, falling back to its `message`
+/// attribute. The messages of multiple failures are joined by an empty line.
+fn finish_failures(testrun: &mut Testrun) {
+    // the details are collected from all the text and CDATA chunks of the body
+    for failure in testrun.failures.iter_mut().chain(&mut testrun.retries) {
+        if let Some(details) = &mut failure.details {
+            let trimmed = details.trim();
+            if trimmed.len() != details.len() {
+                *details = trimmed.to_string();
             }
         }
+    }
+
+    let Some(first) = testrun.failures.first() else {
+        return;
+    };
+    testrun.failure_summary.clone_from(&first.message);
+    testrun.failure_type.clone_from(&first.failure_type);
+    testrun.failure_details.clone_from(&first.details);
+
+    let messages: Vec<&str> = testrun
+        .failures
+        .iter()
+        .filter_map(|failure| failure.details.as_deref().or(failure.message.as_deref()))
+        .collect();
+    if !messages.is_empty() {
+        testrun.failure_message = Some(messages.join("\n\n"));
+    }
+}
+
+/// Fills in the file of a GoogleTest testrun from its failure, and truncates its
+/// failure message and outputs to their limits.
+fn finish_outputs(testrun: &mut Testrun, options: &ParseOptions) {
+    if testrun.framework == Some(Framework::GoogleTest) && testrun.filename.is_none() {
+        let file = testrun
+            .failures
+            .first()
+            .and_then(|failure| failure.message.as_deref())
+            .and_then(googletest_failure_file);
+        testrun.filename = file.and_then(|file| {
+            options
+                .limit_length(normalize_path(file).into_owned(), options.field_limits.file)
+                .ok()
+        });
+    }
+    if let (Some(message), Some(max_length)) = (
+        &mut testrun.failure_message,
+        options.field_limits.failure_message,
+    ) {
+        truncate_middle(message, max_length);
+    }
+    for output in [&mut testrun.system_out, &mut testrun.system_err]
+        .into_iter()
+        .flatten()
+    {
+        truncate_middle(output, options.max_output_length);
+    }
+}
+
+/// Normalizes the files of the `testruns` written on Windows.
+///
+/// This happens only after parsing, as the pytest computed name of a testrun, which can
+/// be recomputed when its framework is detected later on, depends on the original file.
+fn normalize_filenames(testruns: &mut [Testrun], options: &ParseOptions) {
+    for filename in testruns.iter_mut().filter_map(|t| t.filename.as_mut()) {
+        let normalized = normalize_path(filename);
+        if *normalized == **filename {
+            continue;
+        }
+        // normalizing never makes a file longer, so it stays within its limit
+        if let Ok(normalized) =
+            options.limit_length(normalized.into_owned(), options.field_limits.file)
+        {
+            *filename = normalized;
+        }
+    }
+}
+
+/// Records a failed attempt from a `flakyFailure`, `flakyError`, `rerunFailure`
+/// or `rerunError` element.
+fn add_retry(testrun: &mut Testrun, e: &BytesStart) -> Result<()> {
+    let (flaky, outcome) = match e.name().as_ref() {
+        b"flakyFailure" => (true, Outcome::Failure),
+        b"flakyError" => (true, Outcome::Error),
+        b"rerunError" => (false, Outcome::Error),
+        _ => (false, Outcome::Failure),
     };
+
+    testrun.flaky |= flaky;
+    testrun.retries.push(parse_failure(outcome, e)?);
+    testrun.attempts = testrun.retries.len() as u32 + 1;
+
     Ok(())
 }
 
+/// Truncates `output` to roughly `max_length` bytes, by cutting out its middle.
+fn truncate_middle(output: &mut String, max_length: usize) {
+    if output.len() <= max_length {
+        return;
+    }
+
+    let mut head_end = max_length / 2;
+    while !output.is_char_boundary(head_end) {
+        head_end -= 1;
+    }
+    let mut tail_start = output.len() - max_length / 2;
+    while !output.is_char_boundary(tail_start) {
+        tail_start += 1;
+    }
+
+    let marker = format!("\n[... {} bytes truncated ...]\n", tail_start - head_end);
+    output.replace_range(head_end..tail_start, &marker);
+}
+
+/// The `system-out` or `system-err` element that is currently being read.
+#[derive(Clone, Copy)]
+enum OutputKind {
+    SystemOut,
+    SystemErr,
+}
+
+/// The output and properties captured at the `testsuite` level, which are
+/// inherited by the testcases within it.
+struct SuiteContext {
+    /// Index of the first testrun within this testsuite.
+    first_testrun: usize,
+    system_out: Option<String>,
+    system_err: Option<String>,
+    properties: PropertiesValue,
+    /// The framework of the testcases within this testsuite, once it is known.
+    framework: Option<Framework>,
+}
+
+/// Returns the framework of the innermost testsuite, or that of the whole file.
+fn suite_framework(
+    suites: &[SuiteContext],
+    file_framework: Option<Framework>,
+) -> Option<Framework> {
+    match suites.last() {
+        Some(suite) => suite.framework,
+        None => file_framework,
+    }
+}
+
+/// Adopts the framework detected for the last testrun as the framework of its testsuite.
+///
+/// The naming rules of that framework are then also applied to the previous testruns
+/// of the testsuite, for which no framework could be detected.
+fn adopt_framework(
+    testruns: &mut Vec<Testrun>,
+    suites: &mut [SuiteContext],
+    file_framework: &mut Option<Framework>,
+    network: Option<&Network>,
+    options: &ParseOptions,
+    location: u64,
+    warnings: &mut Vec<WarningInfo>,
+) {
+    let Some(framework) = testruns.last().and_then(|t| t.framework) else {
+        return;
+    };
+    let (target, first_testrun) = match suites.last_mut() {
+        Some(suite) => (&mut suite.framework, suite.first_testrun),
+        None => (file_framework, 0),
+    };
+    if target.is_some() {
+        return;
+    }
+    *target = Some(framework);
+
+    let mut i = first_testrun;
+    while i < testruns.len() {
+        let t = &mut testruns[i];
+        if t.framework.is_none() {
+            if let Err(error) =
+                set_computed_name(t, Some(framework), network, options, location, warnings)
+            {
+                warn_skipped_testcase(error, location, warnings);
+                testruns.remove(i);
+                continue;
+            }
+        }
+        i += 1;
+    }
+}
+
+/// Warns about a testcase which is skipped because of the `error`.
+fn warn_skipped_testcase(error: ParseAttrsError, location: u64, warnings: &mut Vec<WarningInfo>) {
+    warnings.push(WarningInfo::new(
+        format!("Warning while parsing testcase attributes: {}", error),
+        location,
+    ));
+}
+
+/// Returns the framework used by most of the `testruns`, and all the frameworks
+/// in order of their first appearance.
+fn summarize_frameworks(testruns: &[Testrun]) -> (Option<Framework>, Vec<Framework>) {
+    let mut counts: Vec<(Framework, usize)> = vec![];
+    for framework in testruns.iter().filter_map(|t| t.framework) {
+        match counts.iter_mut().find(|(f, _)| *f == framework) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((framework, 1)),
+        }
+    }
+
+    // `max_by_key` returns the last maximum, so this prefers the first framework on ties
+    let dominant = counts
+        .iter()
+        .rev()
+        .max_by_key(|(_, count)| *count)
+        .map(|(f, _)| *f);
+    (dominant, counts.into_iter().map(|(f, _)| f).collect())
+}
+
+/// Appends `text` to the output in `target`.
+fn append_output(target: &mut Option<String>, text: &str) {
+    match target {
+        Some(output) => {
+            output.push('\n');
+            output.push_str(text);
+        }
+        None => *target = Some(text.to_string()),
+    }
+}
+
+/// Appends a chunk of the body of a failure to its `details`.
+fn append_details(details: &mut Option<String>, text: &str) {
+    match details {
+        Some(details) => details.push_str(text),
+        None => *details = Some(text.to_string()),
+    }
+}
+
+/// Appends `text` to the currently open testcase, or the innermost testsuite
+/// in case we are not inside of a testcase.
+fn capture_output(
+    kind: OutputKind,
+    text: &str,
+    saved_testrun: &mut Option<TestrunOrSkipped>,
+    suites: &mut [SuiteContext],
+) {
+    let (system_out, system_err) = match saved_testrun {
+        Some(TestrunOrSkipped::Testrun(testrun)) => {
+            (&mut testrun.system_out, &mut testrun.system_err)
+        }
+        Some(TestrunOrSkipped::Skipped) => return,
+        None => match suites.last_mut() {
+            Some(suite) => (&mut suite.system_out, &mut suite.system_err),
+            None => return,
+        },
+    };
+    match kind {
+        OutputKind::SystemOut => append_output(system_out, text),
+        OutputKind::SystemErr => append_output(system_err, text),
+    }
+}
+
+/// Applies the properties and output of a finished testsuite to the testruns within it.
+fn finish_suite(mut suite: SuiteContext, testruns: &mut [Testrun], options: &ParseOptions) {
+    for testrun in &mut testruns[suite.first_testrun..] {
+        inherit_properties(&mut testrun.properties, &suite.properties);
+    }
+
+    // attribute the testsuite output to all the failing testcases within it
+    for output in [&mut suite.system_out, &mut suite.system_err]
+        .into_iter()
+        .flatten()
+    {
+        truncate_middle(output, options.max_output_length);
+    }
+    let failed = testruns[suite.first_testrun..]
+        .iter_mut()
+        .filter(|t| matches!(t.outcome, Outcome::Failure | Outcome::Error));
+    for testrun in failed {
+        if testrun.system_out.is_none() {
+            testrun.system_out.clone_from(&suite.system_out);
+        }
+        if testrun.system_err.is_none() {
+            testrun.system_err.clone_from(&suite.system_err);
+        }
+    }
+}
+
+/// The result of parsing a single JUnit XML file.
+pub struct ParsedJUnit {
+    /// The framework used by most of the testruns.
+    pub framework: Option<Framework>,
+    /// All the frameworks used by the testruns, in order of their first appearance.
+    pub frameworks: Vec<Framework>,
+    pub testruns: Vec<Testrun>,
+    pub warnings: Vec<WarningInfo>,
+    /// Whether the file is truncated or malformed, and only the testcases
+    /// before the error were parsed.
+    pub partial: bool,
+}
+
 pub fn use_reader(
     reader: &mut Reader<&[u8]>,
-    network: Option<&HashSet<String>>,
-) -> PyResult<(Option<Framework>, Vec<Testrun>, Vec<WarningInfo>)> {
+    network: Option<&Network>,
+    options: &ParseOptions,
+) -> PyResult<ParsedJUnit> {
     let mut testruns: Vec<Testrun> = Vec::new();
     let mut saved_testrun: Option<TestrunOrSkipped> = None;
 
     let mut in_failure: bool = false;
     let mut in_error: bool = false;
+    let mut in_output: Option<OutputKind> = None;
+    let mut in_retry: bool = false;
 
-    let mut framework: Option<Framework> = None;
+    // the framework of testcases outside of any testsuite, and the default for all testsuites
+    let mut file_framework: Option<Framework> = None;
 
     let mut warnings: Vec<WarningInfo> = Vec::new();
+    let mut partial = false;
 
     // every time we come across a testsuite element we update this vector:
     // if the testsuite element contains the time attribute append its value to this vec
     // else append a clone of the last value in the vec
     let mut testsuite_names: Vec<Option<ValidatedString>> = vec![];
     let mut testsuite_times: Vec<Option<String>> = vec![];
+    let mut suites: Vec<SuiteContext> = vec![];
 
     let mut buf = Vec::new();
-    loop {
-        let event = reader
-            .read_event_into(&mut buf)
-            .context("Error parsing XML")?;
-        match event {
-            Event::Eof => {
-                break;
-            }
-            Event::Start(e) => match e.name().as_ref() {
-                b"testcase" => {
-                    let attrs = parse_testcase_attrs(e.attributes());
-                    match attrs {
-                        Ok(attrs) => {
-                            let (testrun, parsed_framework) = populate(
-                                attrs,
-                                testsuite_names
-                                    .iter()
-                                    .rev()
-                                    .find_map(|e| e.clone())
-                                    .unwrap_or_default(),
-                                testsuite_times.iter().rev().find_map(|e| e.as_deref()),
-                                framework,
-                                network,
-                            )?;
-                            saved_testrun = Some(TestrunOrSkipped::Testrun(testrun));
-                            framework = parsed_framework;
-                        }
-                        Err(error) => match error {
-                            ParseAttrsError::AttrTooLong(_, _) => {
-                                warnings.push(WarningInfo::new(
-                                    format!("Warning while parsing testcase attributes: {}", error),
-                                    reader.buffer_position() - e.len() as u64,
-                                ));
-                                saved_testrun = Some(TestrunOrSkipped::Skipped);
+    // in lenient mode, any error ends the parsing but keeps the testcases closed before it
+    let mut parse_events = || -> Result<()> {
+        loop {
+            let event = reader
+                .read_event_into(&mut buf)
+                .context("Error parsing XML")?;
+            match event {
+                Event::Eof => {
+                    if saved_testrun.is_some() || !suites.is_empty() {
+                        anyhow::bail!("unexpected end of file before all elements were closed");
+                    }
+                    break;
+                }
+                Event::Start(e) => match e.name().as_ref() {
+                    b"testcase" => {
+                        let location = reader.buffer_position() - e.len() as u64;
+                        let testrun =
+                            parse_testcase_attrs(e.attributes(), options).and_then(|attrs| {
+                                let time = attrs
+                                    .time
+                                    .as_deref()
+                                    .or(testsuite_times.iter().rev().find_map(|e| e.as_deref()));
+                                let duration =
+                                    parse_testcase_duration(time, location, &mut warnings);
+                                populate(
+                                    attrs,
+                                    testsuite_names
+                                        .iter()
+                                        .rev()
+                                        .find_map(|e| e.clone())
+                                        .unwrap_or_default(),
+                                    duration,
+                                    suite_framework(&suites, file_framework),
+                                    network,
+                                    options,
+                                    location,
+                                    &mut warnings,
+                                )
+                            });
+                        match testrun {
+                            Ok(testrun) => {
+                                saved_testrun = Some(TestrunOrSkipped::Testrun(Box::new(testrun)));
                             }
-                            _ => {
-                                Err(anyhow::anyhow!(
-                                    "Error parsing testcase attributes: {}",
-                                    error
-                                ))?;
+                            Err(error) => match error {
+                                ParseAttrsError::AttrTooLong(..) => {
+                                    warn_skipped_testcase(error, location, &mut warnings);
+                                    saved_testrun = Some(TestrunOrSkipped::Skipped);
+                                }
+                                _ => {
+                                    Err(anyhow::anyhow!(
+                                        "Error parsing testcase attributes: {}",
+                                        error
+                                    ))?;
+                                }
+                            },
+                        }
+                    }
+                    b"skipped" => {
+                        let saved = saved_testrun
+                            .as_mut()
+                            .context("Error accessing saved testrun")?;
+                        match saved {
+                            TestrunOrSkipped::Testrun(testrun) => {
+                                testrun.outcome = Outcome::Skip;
                             }
-                        },
+                            TestrunOrSkipped::Skipped => {}
+                        }
                     }
-                }
-                b"skipped" => {
-                    let saved = saved_testrun
-                        .as_mut()
-                        .context("Error accessing saved testrun")?;
-                    match saved {
-                        TestrunOrSkipped::Testrun(testrun) => {
-                            testrun.outcome = Outcome::Skip;
+                    b"error" => {
+                        let saved = saved_testrun
+                            .as_mut()
+                            .context("Error accessing saved testrun")?;
+                        match saved {
+                            TestrunOrSkipped::Testrun(testrun) => {
+                                add_failure(testrun, Outcome::Error, &e)?;
+                            }
+                            TestrunOrSkipped::Skipped => {}
                         }
-                        TestrunOrSkipped::Skipped => {}
+
+                        in_error = true;
                     }
-                }
-                b"error" => {
-                    let saved = saved_testrun
-                        .as_mut()
-                        .context("Error accessing saved testrun")?;
-                    match saved {
-                        TestrunOrSkipped::Testrun(testrun) => {
-                            testrun.outcome = Outcome::Error;
-
-                            testrun.failure_message = get_attribute(&e, "message")?
-                                .map(|failure_message| unescape_str(&failure_message).into());
+                    b"failure" => {
+                        let saved = saved_testrun
+                            .as_mut()
+                            .context("Error accessing saved testrun")?;
+                        match saved {
+                            TestrunOrSkipped::Testrun(testrun) => {
+                                add_failure(testrun, Outcome::Failure, &e)?;
+                            }
+                            TestrunOrSkipped::Skipped => {}
                         }
-                        TestrunOrSkipped::Skipped => {}
+
+                        in_failure = true;
                     }
+                    b"flakyFailure" | b"flakyError" | b"rerunFailure" | b"rerunError" => {
+                        let saved = saved_testrun
+                            .as_mut()
+                            .context("Error accessing saved testrun")?;
+                        match saved {
+                            TestrunOrSkipped::Testrun(testrun) => add_retry(testrun, &e)?,
+                            TestrunOrSkipped::Skipped => {}
+                        }
 
-                    in_error = true;
-                }
-                b"failure" => {
-                    let saved = saved_testrun
-                        .as_mut()
-                        .context("Error accessing saved testrun")?;
-                    match saved {
-                        TestrunOrSkipped::Testrun(testrun) => {
-                            testrun.outcome = Outcome::Failure;
-
-                            testrun.failure_message = get_attribute(&e, "message")?
-                                .map(|failure_message| unescape_str(&failure_message).into());
+                        in_retry = true;
+                    }
+                    b"testsuite" => {
+                        testsuite_names.push(
+                            get_attribute(&e, "name")?
+                                .map(|s| {
+                                    options
+                                        .limit_length(s, options.field_limits.testsuite)
+                                        .context(
+                                            "Error converting testsuite name to ValidatedString",
+                                        )
+                                })
+                                .transpose()?,
+                        );
+                        testsuite_times.push(get_attribute(&e, "time")?);
+                        // nested testsuites start out with the framework of their parent
+                        let framework = suite_framework(&suites, file_framework);
+                        suites.push(SuiteContext {
+                            first_testrun: testruns.len(),
+                            system_out: None,
+                            system_err: None,
+                            properties: PropertiesValue(None),
+                            framework,
+                        });
+                    }
+                    b"testsuites" => {
+                        let testsuites_name = get_attribute(&e, "name")?;
+                        file_framework =
+                            testsuites_name.and_then(|name| check_testsuites_name(&name))
+                    }
+                    b"property" => {
+                        let body = reader.read_text(e.name()).context("Error parsing XML")?;
+                        handle_property_element(
+                            &e,
+                            Some(property_body(&body)),
+                            &mut saved_testrun,
+                            &mut suites,
+                            options,
+                            reader.buffer_position(),
+                            &mut warnings,
+                        )?
+                    }
+                    b"system-out" => in_output = Some(OutputKind::SystemOut),
+                    b"system-err" => in_output = Some(OutputKind::SystemErr),
+                    _ => {}
+                },
+                Event::End(e) => match e.name().as_ref() {
+                    b"testcase" => {
+                        let saved = saved_testrun.take().context(
+                            "Met testcase closing tag without first meeting testcase opening tag",
+                        )?;
+                        match saved {
+                            TestrunOrSkipped::Testrun(mut testrun) => {
+                                finish_failures(&mut testrun);
+                                // the failure output, like a JVM stack trace, can reveal the framework
+                                let detected = match testrun.framework {
+                                    None => testrun.detect_framework(),
+                                    // only a stack trace tells Kotest apart from JUnit tests in Kotlin
+                                    Some(Framework::JUnit) => testrun
+                                        .detect_framework()
+                                        .filter(|framework| *framework == Framework::Kotest),
+                                    Some(_) => None,
+                                };
+                                // the output of a JUnit 5 invocation can reveal its test method
+                                let detected = detected.or(testrun.framework.filter(|framework| {
+                                    junit5_invocation(&testrun, Some(*framework)).is_some()
+                                }));
+                                let renamed = match detected {
+                                    Some(framework) => set_computed_name(
+                                        &mut testrun,
+                                        Some(framework),
+                                        network,
+                                        options,
+                                        reader.buffer_position(),
+                                        &mut warnings,
+                                    ),
+                                    None => Ok(()),
+                                };
+                                match renamed {
+                                    Ok(()) => {
+                                        finish_outputs(&mut testrun, options);
+                                        testruns.push(*testrun);
+                                        adopt_framework(
+                                            &mut testruns,
+                                            &mut suites,
+                                            &mut file_framework,
+                                            network,
+                                            options,
+                                            reader.buffer_position(),
+                                            &mut warnings,
+                                        );
+                                    }
+                                    Err(error) => warn_skipped_testcase(
+                                        error,
+                                        reader.buffer_position(),
+                                        &mut warnings,
+                                    ),
+                                }
+                            }
+                            TestrunOrSkipped::Skipped => {}
                         }
-                        TestrunOrSkipped::Skipped => {}
                     }
+                    b"failure" => in_failure = false,
+                    b"error" => in_error = false,
+                    b"system-out" | b"system-err" => in_output = None,
+                    b"flakyFailure" | b"flakyError" | b"rerunFailure" | b"rerunError" => {
+                        in_retry = false
+                    }
+                    b"testsuite" => {
+                        testsuite_times.pop();
+                        testsuite_names.pop();
 
-                    in_failure = true;
-                }
-                b"testsuite" => {
-                    testsuite_names.push(
-                        get_attribute(&e, "name")?
-                            .map(|s| {
-                                ValidatedString::from_string(s)
-                                    .context("Error converting testsuite name to ValidatedString")
-                            })
-                            .transpose()?,
-                    );
-                    testsuite_times.push(get_attribute(&e, "time")?);
-                }
-                b"testsuites" => {
-                    let testsuites_name = get_attribute(&e, "name")?;
-                    framework = testsuites_name.and_then(|name| check_testsuites_name(&name))
-                }
-                b"property" => handle_property_element(
-                    &e,
-                    &mut saved_testrun,
-                    reader.buffer_position(),
-                    &mut warnings,
-                )?,
-                _ => {}
-            },
-            Event::End(e) => match e.name().as_ref() {
-                b"testcase" => {
-                    let saved = saved_testrun.take().context(
-                        "Met testcase closing tag without first meeting testcase opening tag",
-                    )?;
-                    match saved {
-                        TestrunOrSkipped::Testrun(testrun) => testruns.push(testrun),
-                        TestrunOrSkipped::Skipped => {}
+                        if let Some(suite) = suites.pop() {
+                            finish_suite(suite, &mut testruns, options);
+                        }
                     }
-                }
-                b"failure" => in_failure = false,
-                b"error" => in_error = false,
-                b"testsuite" => {
-                    testsuite_times.pop();
-                    testsuite_names.pop();
-                }
-                _ => (),
-            },
-            Event::Empty(e) => match e.name().as_ref() {
-                b"testcase" => {
-                    let attrs = parse_testcase_attrs(e.attributes());
-                    match attrs {
-                        Ok(attrs) => {
-                            let (testrun, parsed_framework) = populate(
-                                attrs,
-                                testsuite_names
-                                    .iter()
-                                    .rev()
-                                    .find_map(|e| e.clone())
-                                    .unwrap_or_default(),
-                                testsuite_times.iter().rev().find_map(|e| e.as_deref()),
-                                framework,
-                                network,
-                            )?;
-                            testruns.push(testrun);
-                            framework = parsed_framework;
+                    _ => (),
+                },
+                Event::Empty(e) => match e.name().as_ref() {
+                    b"testcase" => {
+                        let location = reader.buffer_position() - e.len() as u64;
+                        let testrun =
+                            parse_testcase_attrs(e.attributes(), options).and_then(|attrs| {
+                                let time = attrs
+                                    .time
+                                    .as_deref()
+                                    .or(testsuite_times.iter().rev().find_map(|e| e.as_deref()));
+                                let duration =
+                                    parse_testcase_duration(time, location, &mut warnings);
+                                populate(
+                                    attrs,
+                                    testsuite_names
+                                        .iter()
+                                        .rev()
+                                        .find_map(|e| e.clone())
+                                        .unwrap_or_default(),
+                                    duration,
+                                    suite_framework(&suites, file_framework),
+                                    network,
+                                    options,
+                                    location,
+                                    &mut warnings,
+                                )
+                            });
+                        match testrun {
+                            Ok(testrun) => {
+                                testruns.push(testrun);
+                                adopt_framework(
+                                    &mut testruns,
+                                    &mut suites,
+                                    &mut file_framework,
+                                    network,
+                                    options,
+                                    reader.buffer_position(),
+                                    &mut warnings,
+                                );
+                            }
+                            Err(error) => match error {
+                                ParseAttrsError::AttrTooLong(..) => {
+                                    warn_skipped_testcase(error, location, &mut warnings);
+                                }
+                                _ => Err(anyhow::anyhow!(
+                                    "Error parsing testcase attributes: {}",
+                                    error
+                                ))?,
+                            },
                         }
-                        Err(error) => match error {
-                            ParseAttrsError::AttrTooLong(_, _) => {
-                                warnings.push(WarningInfo::new(
-                                    format!("Warning while parsing testcase attributes: {}", error),
-                                    reader.buffer_position() - e.len() as u64,
-                                ));
+                    }
+                    b"failure" => {
+                        let saved = saved_testrun
+                            .as_mut()
+                            .context("Error accessing saved testrun")?;
+                        match saved {
+                            TestrunOrSkipped::Testrun(testrun) => {
+                                add_failure(testrun, Outcome::Failure, &e)?;
                             }
-                            _ => Err(anyhow::anyhow!(
-                                "Error parsing testcase attributes: {}",
-                                error
-                            ))?,
-                        },
+                            TestrunOrSkipped::Skipped => {}
+                        }
                     }
-                }
-                b"failure" => {
-                    let saved = saved_testrun
-                        .as_mut()
-                        .context("Error accessing saved testrun")?;
-                    match saved {
-                        TestrunOrSkipped::Testrun(testrun) => {
-                            testrun.outcome = Outcome::Failure;
-
-                            testrun.failure_message = get_attribute(&e, "message")?
-                                .map(|failure_message| unescape_str(&failure_message).into());
+                    b"flakyFailure" | b"flakyError" | b"rerunFailure" | b"rerunError" => {
+                        let saved = saved_testrun
+                            .as_mut()
+                            .context("Error accessing saved testrun")?;
+                        match saved {
+                            TestrunOrSkipped::Testrun(testrun) => add_retry(testrun, &e)?,
+                            TestrunOrSkipped::Skipped => {}
                         }
-                        TestrunOrSkipped::Skipped => {}
                     }
-                }
-                b"skipped" => {
-                    let saved = saved_testrun
-                        .as_mut()
-                        .context("Error accessing saved testrun")?;
-                    match saved {
-                        TestrunOrSkipped::Testrun(testrun) => {
-                            testrun.outcome = Outcome::Skip;
+                    b"skipped" => {
+                        let saved = saved_testrun
+                            .as_mut()
+                            .context("Error accessing saved testrun")?;
+                        match saved {
+                            TestrunOrSkipped::Testrun(testrun) => {
+                                testrun.outcome = Outcome::Skip;
+                            }
+                            TestrunOrSkipped::Skipped => {}
                         }
-                        TestrunOrSkipped::Skipped => {}
                     }
-                }
-                b"error" => {
-                    let saved = saved_testrun
-                        .as_mut()
-                        .context("Error accessing saved testrun")?;
-                    match saved {
-                        TestrunOrSkipped::Testrun(testrun) => {
-                            testrun.outcome = Outcome::Error;
-
-                            testrun.failure_message = get_attribute(&e, "message")?
-                                .map(|failure_message| unescape_str(&failure_message).into());
+                    b"error" => {
+                        let saved = saved_testrun
+                            .as_mut()
+                            .context("Error accessing saved testrun")?;
+                        match saved {
+                            TestrunOrSkipped::Testrun(testrun) => {
+                                add_failure(testrun, Outcome::Error, &e)?;
+                            }
+                            TestrunOrSkipped::Skipped => {}
                         }
-                        TestrunOrSkipped::Skipped => {}
                     }
-                }
-                b"property" => handle_property_element(
-                    &e,
-                    &mut saved_testrun,
-                    reader.buffer_position(),
-                    &mut warnings,
-                )?,
-                _ => {}
-            },
-            Event::Text(mut xml_failure_message) => {
-                if in_failure || in_error {
-                    let saved = saved_testrun
-                        .as_mut()
-                        .context("Error accessing saved testrun")?;
-                    match saved {
-                        TestrunOrSkipped::Testrun(testrun) => {
-                            xml_failure_message.inplace_trim_end();
-                            xml_failure_message.inplace_trim_start();
-
-                            testrun.failure_message = Some(
-                                unescape_str(std::str::from_utf8(&xml_failure_message)?).into(),
+                    b"property" => handle_property_element(
+                        &e,
+                        None,
+                        &mut saved_testrun,
+                        &mut suites,
+                        options,
+                        reader.buffer_position(),
+                        &mut warnings,
+                    )?,
+                    _ => {}
+                },
+                Event::Text(mut xml_failure_message) => {
+                    if in_failure || in_error {
+                        let saved = saved_testrun
+                            .as_mut()
+                            .context("Error accessing saved testrun")?;
+                        match saved {
+                            TestrunOrSkipped::Testrun(testrun) => {
+                                xml_failure_message.inplace_trim_end();
+                                xml_failure_message.inplace_trim_start();
+
+                                if let Some(failure) = testrun.failures.last_mut() {
+                                    append_details(
+                                        &mut failure.details,
+                                        &unescape_str(std::str::from_utf8(&xml_failure_message)?),
+                                    );
+                                }
+                            }
+                            TestrunOrSkipped::Skipped => {}
+                        }
+                    } else if in_retry {
+                        // the failure details are commonly nested in a `stackTrace` element,
+                        // any other output of the failed attempt is ignored
+                        let retry = match saved_testrun.as_mut() {
+                            Some(TestrunOrSkipped::Testrun(testrun)) if in_output.is_none() => {
+                                testrun.retries.last_mut()
+                            }
+                            _ => None,
+                        };
+                        if let Some(retry) = retry {
+                            append_details(
+                                &mut retry.details,
+                                &unescape_str(std::str::from_utf8(&xml_failure_message)?),
                             );
                         }
-                        TestrunOrSkipped::Skipped => {}
+                    } else if let Some(kind) = in_output {
+                        let text = std::str::from_utf8(&xml_failure_message)?;
+                        capture_output(kind, &unescape_str(text), &mut saved_testrun, &mut suites);
+                    }
+                }
+                Event::CData(cdata) => {
+                    if in_failure || in_error {
+                        // JVM test frameworks wrap their stack traces in CDATA
+                        if let Some(TestrunOrSkipped::Testrun(testrun)) = saved_testrun.as_mut() {
+                            if let Some(failure) = testrun.failures.last_mut() {
+                                append_details(&mut failure.details, std::str::from_utf8(&cdata)?);
+                            }
+                        }
+                    } else if in_retry {
+                        let retry = match saved_testrun.as_mut() {
+                            Some(TestrunOrSkipped::Testrun(testrun)) if in_output.is_none() => {
+                                testrun.retries.last_mut()
+                            }
+                            _ => None,
+                        };
+                        if let Some(retry) = retry {
+                            append_details(&mut retry.details, std::str::from_utf8(&cdata)?);
+                        }
+                    } else if let Some(kind) = in_output {
+                        // output of failed attempts is not captured
+                        let text = std::str::from_utf8(&cdata)?;
+                        capture_output(kind, text, &mut saved_testrun, &mut suites);
                     }
                 }
+
+                // There are several other `Event`s we do not consider here
+                _ => (),
             }
+            buf.clear()
+        }
+        Ok(())
+    };
 
-            // There are several other `Event`s we do not consider here
-            _ => (),
+    if let Err(error) = parse_events() {
+        if !options.lenient {
+            return Err(error.into());
         }
-        buf.clear()
+        warnings.push(WarningInfo::new(
+            format!("File was only partially parsed: {:#}", error),
+            reader.buffer_position(),
+        ));
+        partial = true;
+    }
+
+    // the testsuites of a partially parsed file are never closed
+    while let Some(suite) = suites.pop() {
+        finish_suite(suite, &mut testruns, options);
     }
+    normalize_filenames(&mut testruns, options);
 
-    Ok((framework, testruns, warnings))
+    let (framework, frameworks) = summarize_frameworks(&testruns);
+    Ok(ParsedJUnit {
+        // files without testcases can still have a framework from their `testsuites` name
+        framework: framework.or(file_framework),
+        frameworks,
+        testruns,
+        warnings,
+        partial,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::naming::{NameTemplate, NamingRules, Rewrite};
+    use crate::options::LengthPolicy;
+    use regex::Regex;
+
+    fn parse(
+        xml: &[u8],
+        network: Option<&Network>,
+        options: &ParseOptions,
+    ) -> PyResult<ParsedJUnit> {
+        let mut reader = Reader::from_reader(xml);
+        reader.config_mut().trim_text(true);
+        use_reader(&mut reader, network, options)
+    }
+
+    #[test]
+    fn test_googletest_failure_file() {
+        assert_eq!(
+            googletest_failure_file("test/math_test.cc:7\nExpected equality of these values:"),
+            Some("test/math_test.cc")
+        );
+        assert_eq!(
+            googletest_failure_file("C:\\src\\math_test.cc(7): error: Value of: false"),
+            None
+        );
+        assert_eq!(
+            googletest_failure_file("C:\\src\\math_test.cc(7)\nValue of: false"),
+            Some("C:\\src\\math_test.cc")
+        );
+        assert_eq!(googletest_failure_file("Expected: 1"), None);
+    }
+
+    #[test]
+    fn test_truncate_middle() {
+        let mut output = "abcdefghij".to_string();
+        truncate_middle(&mut output, 20);
+        assert_eq!(output, "abcdefghij");
+
+        truncate_middle(&mut output, 4);
+        assert_eq!(output, "ab\n[... 6 bytes truncated ...]\nij");
+
+        // the truncation does not split multi-byte characters
+        let mut output = "äöüäöü".to_string();
+        truncate_middle(&mut output, 5);
+        assert_eq!(output, "ä\n[... 8 bytes truncated ...]\nü");
+    }
+
+    #[test]
+    fn test_properties() {
+        let xml = br#"<testsuites>
+            <testsuite name="outer">
+                <properties>
+                    <property name="owner" value="team-a" />
+                    <property name="evals.run.name" value="run" />
+                </properties>
+                <testsuite name="inner">
+                    <properties>
+                        <property name="owner" value="team-b" />
+                    </properties>
+                    <testcase name="case">
+                        <properties>
+                            <property name="requirement" value="REQ-1" />
+                            <property name="requirement" value="REQ-2" />
+                            <property name="evals.item.id" value="abc" />
+                            <property name="secret" value="hunter2" />
+                            <property name="run">body &amp; text</property>
+                            <property name="log"><![CDATA[multi
+line]]></property>
+                            <property name="no-value" />
+                        </properties>
+                    </testcase>
+                </testsuite>
+            </testsuite>
+        </testsuites>"#;
+        let options = ParseOptions {
+            property_denylist: vec!["secret".into()],
+            ..Default::default()
+        };
+        let ParsedJUnit {
+            testruns, warnings, ..
+        } = parse(xml, None, &options).unwrap();
+
+        assert_eq!(
+            testruns[0].properties.0,
+            Some(serde_json::json!({
+                "properties": {
+                    "requirement": ["REQ-1", "REQ-2"],
+                    "run": "body & text",
+                    "log": "multi\nline",
+                    "owner": "team-b",
+                },
+                "item": {"id": "abc"},
+                "run": {"name": "run"},
+            }))
+        );
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].message,
+            "Error parsing `property` element: Property must have value attribute"
+        );
+    }
+
+    #[test]
+    fn test_lenient_keeps_testcases_before_error() {
+        let xml = b"<testsuite name=\"suite\">
+            <testcase name=\"first\" />
+            <testcase name=\"second\"><failure><![CDATA[\xff]]></failure></testcase>
+            <testcase name=\"third\" />
+        </testsuite>";
+        assert!(parse(xml, None, &ParseOptions::default()).is_err());
+
+        let options = ParseOptions {
+            lenient: true,
+            ..Default::default()
+        };
+        let parsed = parse(xml, None, &options).unwrap();
+        assert!(parsed.partial);
+        let names: Vec<_> = parsed.testruns.iter().map(|t| &*t.name).collect();
+        assert_eq!(names, ["first"]);
+        assert_eq!(parsed.warnings.len(), 1);
+        assert!(
+            parsed.warnings[0]
+                .message
+                .starts_with("File was only partially parsed: invalid utf-8"),
+            "{}",
+            parsed.warnings[0].message
+        );
+    }
+
+    #[test]
+    fn test_field_limits() {
+        let xml = format!(
+            r#"<testsuite name="suite">
+                <testcase name="test[{}]" classname="a" />
+                <testcase name="short" classname="a"><failure>{}</failure></testcase>
+            </testsuite>"#,
+            "x".repeat(1500),
+            "y".repeat(100),
+        );
+        let parse = |options: &ParseOptions| parse(xml.as_bytes(), None, options).unwrap();
+
+        let parsed = parse(&ParseOptions::default());
+        assert_eq!(parsed.testruns.len(), 1);
+        assert_eq!(
+            parsed.warnings[0].message,
+            "Warning while parsing testcase attributes: Limit of string is 1000 chars, for name, we got 1506"
+        );
+
+        // the computed name is still over its limit, which skips only that testcase
+        let mut options = ParseOptions::default();
+        options.field_limits.name = 2000;
+        let parsed = parse(&options);
+        assert_eq!(parsed.testruns.len(), 1);
+        assert_eq!(&*parsed.testruns[0].name, "short");
+        assert_eq!(
+            parsed.warnings[0].message,
+            "Warning while parsing testcase attributes: Limit of string is 1000 chars, for computed_name, we got 1509"
+        );
+
+        options.field_limits.computed_name = 2000;
+        options.field_limits.failure_message = Some(50);
+        let parsed = parse(&options);
+        assert_eq!(parsed.testruns.len(), 2);
+        assert_eq!(parsed.testruns[0].name.len(), 1506);
+        assert!(parsed.testruns[1].failure_message.as_ref().unwrap().len() < 100);
+
+        let options = ParseOptions {
+            length_policy: LengthPolicy::Truncate,
+            ..Default::default()
+        };
+        let parsed = parse(&options);
+        assert!(parsed.warnings.is_empty());
+        assert_eq!(parsed.testruns.len(), 2);
+        assert_eq!(parsed.testruns[0].name.len(), 1000);
+        assert_eq!(parsed.testruns[0].computed_name.len(), 1000);
+    }
+
+    #[test]
+    fn test_naming_rules() {
+        let xml = br#"<testsuites>
+            <testsuite name="suite">
+                <testcase name="test_a[1]" classname="tests.test_a" file="tests/test_a.py" />
+            </testsuite>
+            <testsuite name="other">
+                <testcase name="renders" classname="App" />
+            </testsuite>
+        </testsuites>"#;
+        let parse = |options: &ParseOptions| parse(xml, None, options).unwrap();
+
+        let parsed = parse(&ParseOptions::default());
+        assert_eq!(
+            &*parsed.testruns[0].computed_name,
+            "tests/test_a.py::test_a[1]"
+        );
+        assert_eq!(&*parsed.testruns[1].computed_name, "App::renders");
+
+        let options = ParseOptions {
+            naming_rules: NamingRules {
+                templates: vec![(
+                    Framework::Pytest,
+                    NameTemplate::parse("{testsuite}/{file}::{name}").unwrap(),
+                )],
+                default_template: Some(NameTemplate::parse("{classname}.{name}").unwrap()),
+                rewrites: vec![Rewrite {
+                    pattern: Regex::new(r"\[(\d+)\]$").unwrap(),
+                    replacement: "#$1".into(),
+                }],
+            },
+            ..Default::default()
+        };
+        let parsed = parse(&options);
+        assert_eq!(
+            &*parsed.testruns[0].computed_name,
+            "suite/tests/test_a.py::test_a#1"
+        );
+        assert_eq!(&*parsed.testruns[1].computed_name, "App.renders");
+    }
+
+    #[test]
+    fn test_network_ambiguity() {
+        let xml = br#"<testsuite name="pytest">
+            <testcase name="test_a" classname="tests.test_calc" />
+            <testcase name="test_b" classname="tests.test_calc" />
+            <testcase name="test_c" classname="tests.test_other.TestC" />
+        </testsuite>"#;
+        let paths = [
+            "api/tests/test_calc.py",
+            "web/tests/test_calc.py",
+            "src/tests/test_other.py",
+        ];
+        let parse = |roots: &[String]| {
+            let network = Network::new(paths.map(String::from), roots);
+            parse(xml, Some(&network), &ParseOptions::default()).unwrap()
+        };
+
+        let parsed = parse(&[]);
+        let names: Vec<_> = parsed.testruns.iter().map(|t| &*t.computed_name).collect();
+        assert_eq!(
+            names,
+            [
+                "api/tests/test_calc.py::test_a",
+                "api/tests/test_calc.py::test_b",
+                "src/tests/test_other.py::TestC::test_c"
+            ]
+        );
+        assert_eq!(parsed.warnings.len(), 1);
+        assert_eq!(
+            parsed.warnings[0].message,
+            "Classname `tests.test_calc` matches several files of the network, chose `api/tests/test_calc.py` over `web/tests/test_calc.py`"
+        );
+
+        let parsed = parse(&["web".to_string()]);
+        assert_eq!(
+            &*parsed.testruns[0].computed_name,
+            "web/tests/test_calc.py::test_a"
+        );
+        assert!(parsed.warnings.is_empty());
+    }
 }
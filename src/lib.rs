@@ -3,13 +3,20 @@ use pyo3::prelude::*;
 
 pub mod binary;
 mod compute_name;
+mod duration;
+mod encoding;
 mod failure_message;
 mod junit;
+mod naming;
+mod network;
+mod options;
 mod raw_upload;
 mod testrun;
 mod validated_string;
 mod warning;
 
+pub use naming::{NameTemplate, NamingRules, Rewrite};
+pub use options::{FieldLimits, LengthPolicy, ParseOptions};
 pub use testrun::{Outcome, PropertiesValue, Testrun};
 pub use validated_string::ValidatedString;
 pyo3::create_exception!(test_results_parser, ComputeNameError, PyException);
@@ -0,0 +1,249 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use regex::Regex;
+
+use crate::testrun::Framework;
+
+/// The fields of a testcase which can be used within a [`NameTemplate`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Field {
+    Classname,
+    Name,
+    File,
+    Testsuite,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Literal(String),
+    Field(Field),
+}
+
+/// A template for the computed name of a testcase, like `{file}::{name}`.
+///
+/// The `{classname}`, `{name}`, `{file}` and `{testsuite}` placeholders are replaced
+/// with the fields of the testcase, and `{{` and `}}` stand for literal braces.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NameTemplate {
+    segments: Vec<Segment>,
+}
+
+impl NameTemplate {
+    pub fn parse(template: &str) -> Result<Self, String> {
+        let mut segments = vec![];
+        let mut literal = String::new();
+        let mut chars = template.chars();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.as_str().starts_with('{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.as_str().starts_with('}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let rest = chars.as_str();
+                    let (placeholder, remainder) = rest
+                        .split_once('}')
+                        .ok_or_else(|| format!("Unclosed placeholder in `{}`", template))?;
+                    let field = match placeholder {
+                        "classname" => Field::Classname,
+                        "name" => Field::Name,
+                        "file" => Field::File,
+                        "testsuite" => Field::Testsuite,
+                        _ => {
+                            return Err(format!(
+                                "Unknown placeholder `{{{}}}` in `{}`",
+                                placeholder, template
+                            ))
+                        }
+                    };
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(Segment::Field(field));
+                    chars = remainder.chars();
+                }
+                '}' => return Err(format!("Unmatched `}}` in `{}`", template)),
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        Ok(Self { segments })
+    }
+
+    /// Renders the template, using an empty string for a missing `file`.
+    pub fn render(
+        &self,
+        classname: &str,
+        name: &str,
+        file: Option<&str>,
+        testsuite: &str,
+    ) -> String {
+        let mut rendered = String::new();
+        for segment in &self.segments {
+            rendered.push_str(match segment {
+                Segment::Literal(literal) => literal,
+                Segment::Field(Field::Classname) => classname,
+                Segment::Field(Field::Name) => name,
+                Segment::Field(Field::File) => file.unwrap_or_default(),
+                Segment::Field(Field::Testsuite) => testsuite,
+            });
+        }
+        rendered
+    }
+}
+
+/// A regex replacement applied to the computed names of all testcases.
+#[derive(Debug, Clone)]
+pub struct Rewrite {
+    pub pattern: Regex,
+    /// The replacement, which can refer to capture groups like `$1` or `${name}`.
+    pub replacement: String,
+}
+
+impl PartialEq for Rewrite {
+    fn eq(&self, other: &Self) -> bool {
+        self.pattern.as_str() == other.pattern.as_str() && self.replacement == other.replacement
+    }
+}
+
+impl<'py> FromPyObject<'py> for Rewrite {
+    fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
+        let dict = ob.downcast::<PyDict>()?;
+        let pattern: String = dict
+            .get_item("pattern")?
+            .ok_or_else(|| PyValueError::new_err("Missing `pattern` in naming rewrite"))?
+            .extract()?;
+        let pattern = Regex::new(&pattern).map_err(|err| {
+            PyValueError::new_err(format!("Invalid naming rewrite pattern: {}", err))
+        })?;
+        let replacement = match dict.get_item("replacement")? {
+            Some(replacement) => replacement.extract()?,
+            None => String::new(),
+        };
+
+        Ok(Self {
+            pattern,
+            replacement,
+        })
+    }
+}
+
+/// User-configured rules for the computed names of testcases.
+///
+/// Without any rules, the built-in naming scheme of each framework is used.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct NamingRules {
+    /// The templates for specific frameworks.
+    pub templates: Vec<(Framework, NameTemplate)>,
+    /// The template for all other testcases, including those without a framework.
+    pub default_template: Option<NameTemplate>,
+    /// Rewrites applied in order to the computed name, after the template.
+    pub rewrites: Vec<Rewrite>,
+}
+
+impl NamingRules {
+    /// Returns the template configured for testcases of the given `framework`.
+    pub fn template_for(&self, framework: Option<Framework>) -> Option<&NameTemplate> {
+        self.templates
+            .iter()
+            .find(|(f, _)| Some(*f) == framework)
+            .map(|(_, template)| template)
+            .or(self.default_template.as_ref())
+    }
+
+    /// Applies all the rewrites to the computed `name`.
+    pub fn rewrite(&self, mut name: String) -> String {
+        for rewrite in &self.rewrites {
+            if let std::borrow::Cow::Owned(rewritten) =
+                rewrite.pattern.replace_all(&name, &rewrite.replacement)
+            {
+                name = rewritten;
+            }
+        }
+        name
+    }
+}
+
+impl<'py> FromPyObject<'py> for NamingRules {
+    fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
+        let dict = ob.downcast::<PyDict>()?;
+        let mut rules = NamingRules::default();
+
+        if let Some(templates) = dict.get_item("templates")? {
+            for (key, template) in templates.downcast::<PyDict>()?.iter() {
+                let template = NameTemplate::parse(&template.extract::<String>()?)
+                    .map_err(PyValueError::new_err)?;
+                if key.extract::<&str>()? == "default" {
+                    rules.default_template = Some(template);
+                } else {
+                    rules.templates.push((key.extract()?, template));
+                }
+            }
+        }
+        if let Some(rewrites) = dict.get_item("rewrites")? {
+            rules.rewrites = rewrites.extract()?;
+        }
+
+        Ok(rules)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_template() {
+        let template = NameTemplate::parse("{file}::{classname}.{name} {{{testsuite}}}").unwrap();
+        assert_eq!(
+            template.render("a.B", "test_c", Some("a.py"), "suite"),
+            "a.py::a.B.test_c {suite}"
+        );
+        assert_eq!(
+            template.render("a.B", "test_c", None, ""),
+            "::a.B.test_c {}"
+        );
+
+        assert!(NameTemplate::parse("{unknown}").is_err());
+        assert!(NameTemplate::parse("{name").is_err());
+        assert!(NameTemplate::parse("name}").is_err());
+    }
+
+    #[test]
+    fn test_rules() {
+        let rules = NamingRules {
+            templates: vec![(
+                Framework::Pytest,
+                NameTemplate::parse("{classname}::{name}").unwrap(),
+            )],
+            default_template: None,
+            rewrites: vec![
+                Rewrite {
+                    pattern: Regex::new(r"^tests\.").unwrap(),
+                    replacement: String::new(),
+                },
+                Rewrite {
+                    pattern: Regex::new(r"\[(\d+)\]$").unwrap(),
+                    replacement: "[id=$1]".into(),
+                },
+            ],
+        };
+        assert!(rules.template_for(Some(Framework::Pytest)).is_some());
+        assert!(rules.template_for(Some(Framework::Jest)).is_none());
+        assert!(rules.template_for(None).is_none());
+
+        assert_eq!(
+            rules.rewrite("tests.unit.test_a::test_b[1]".into()),
+            "unit.test_a::test_b[id=1]"
+        );
+    }
+}
@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+
+use crate::compute_name::normalize_path;
+
+/// The files of the repository, as listed in the `network` section of an upload.
+///
+/// This is used to find the file of a pytest testcase when its testsuite does not
+/// include a `file` attribute.
+#[derive(Debug, Clone, Default)]
+pub struct Network {
+    /// The normalized paths of the files, keyed and sorted by their file name.
+    by_file_name: HashMap<String, Vec<String>>,
+    /// The directories which contain the root packages, in order of preference.
+    roots: Vec<String>,
+}
+
+/// A file of the [`Network`] matching the module path of a classname.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetworkMatch<'n> {
+    /// The path of the matched file.
+    pub path: &'n str,
+    /// The number of components of the classname which make up the module path.
+    pub module_len: usize,
+    /// Other files which matched equally well, and were not chosen.
+    pub ambiguous: Vec<&'n str>,
+}
+
+impl Network {
+    pub fn new(paths: impl IntoIterator<Item = String>, roots: &[String]) -> Self {
+        let mut by_file_name: HashMap<String, Vec<String>> = HashMap::new();
+        for path in paths {
+            let path = normalize_path(&path).into_owned();
+            let file_name = path.rsplit('/').next().unwrap_or_default().to_string();
+            by_file_name.entry(file_name).or_default().push(path);
+        }
+        for paths in by_file_name.values_mut() {
+            paths.sort();
+            paths.dedup();
+        }
+
+        let roots = roots
+            .iter()
+            .map(|root| {
+                let root = normalize_path(root);
+                let root = root.trim_start_matches("./").trim_end_matches('/');
+                root.to_string()
+            })
+            .collect();
+
+        Self {
+            by_file_name,
+            roots,
+        }
+    }
+
+    /// Finds the file which defines the module of the given dotted `classname`.
+    ///
+    /// The longest module path is preferred, like `a/b.py` over `a.py` for `a.b.C`.
+    /// A file matches if its path is the module path, the module path within one of
+    /// the `roots`, or ends with the module path, in this order of preference.
+    /// Files which match equally well are ordered by the length of their path, and
+    /// then alphabetically, and are reported as `ambiguous`.
+    pub fn resolve(&self, classname: &str) -> Option<NetworkMatch<'_>> {
+        let components = classname.split('.').collect::<Vec<_>>();
+
+        (1..=components.len()).rev().find_map(|module_len| {
+            let module_path = format!("{}.py", components[..module_len].join("/"));
+            let file_name = components[module_len - 1];
+            let paths = self.by_file_name.get(&format!("{}.py", file_name))?;
+
+            let mut matches = paths
+                .iter()
+                .filter_map(|path| Some((self.rank(path, &module_path)?, path.as_str())))
+                .collect::<Vec<_>>();
+            matches.sort_by(|(a_rank, a), (b_rank, b)| {
+                (a_rank, a.len(), a).cmp(&(b_rank, b.len(), b))
+            });
+
+            let (best_rank, path) = *matches.first()?;
+            let ambiguous = matches[1..]
+                .iter()
+                .take_while(|(rank, _)| *rank == best_rank)
+                .map(|(_, path)| *path)
+                .collect();
+            Some(NetworkMatch {
+                path,
+                module_len,
+                ambiguous,
+            })
+        })
+    }
+
+    /// Ranks how well the `path` matches the `module_path`, lower being better.
+    fn rank(&self, path: &str, module_path: &str) -> Option<usize> {
+        if path == module_path {
+            return Some(0);
+        }
+        let prefix = path.strip_suffix(module_path)?.strip_suffix('/')?;
+        let root = self.roots.iter().position(|root| root == prefix);
+        Some(1 + root.unwrap_or(self.roots.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn network(paths: &[&str], roots: &[&str]) -> Network {
+        let roots = roots
+            .iter()
+            .map(|root| root.to_string())
+            .collect::<Vec<_>>();
+        Network::new(paths.iter().map(|path| path.to_string()), &roots)
+    }
+
+    #[test]
+    fn test_resolve() {
+        let n = network(&["a/b.py", "a.py", "src/a/c.py", r"lib\d\a\c.py"], &[]);
+
+        let m = n.resolve("a.b.C").unwrap();
+        assert_eq!((m.path, m.module_len), ("a/b.py", 2));
+        assert!(m.ambiguous.is_empty());
+
+        let m = n.resolve("a.c").unwrap();
+        assert_eq!((m.path, m.module_len), ("src/a/c.py", 2));
+        assert_eq!(m.ambiguous, ["lib/d/a/c.py"]);
+
+        assert_eq!(n.resolve("a.x").unwrap().path, "a.py");
+        assert!(n.resolve("x.y").is_none());
+        // only whole directories match
+        assert!(network(&["xa/b.py"], &[]).resolve("a.b").is_none());
+    }
+
+    #[test]
+    fn test_resolve_roots() {
+        let paths = ["a/c.py", "lib/a/c.py", "src/a/c.py"];
+
+        let n = network(&paths[1..], &["./src/"]);
+        let m = n.resolve("a.c").unwrap();
+        assert_eq!(m.path, "src/a/c.py");
+        assert!(m.ambiguous.is_empty());
+
+        let n = network(&paths, &["src", "lib"]);
+        let m = n.resolve("a.c").unwrap();
+        assert_eq!(m.path, "a/c.py");
+        assert!(m.ambiguous.is_empty());
+    }
+}
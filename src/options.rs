@@ -0,0 +1,215 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::naming::NamingRules;
+use crate::validated_string::{ValidatedString, DEFAULT_MAX_LENGTH};
+
+/// The default limit for captured `system-out` and `system-err` output, in bytes.
+const DEFAULT_MAX_OUTPUT_LENGTH: usize = 16 * 1024;
+
+/// What to do with a testcase that has a field exceeding its [`FieldLimits`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LengthPolicy {
+    /// The testcase is skipped, with a warning.
+    #[default]
+    Skip,
+    /// The field is truncated, and ends in a hash of its complete value to keep it unique.
+    Truncate,
+}
+
+impl<'py> FromPyObject<'py> for LengthPolicy {
+    fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
+        let s = ob.extract::<&str>()?;
+        match s {
+            "skip" => Ok(LengthPolicy::Skip),
+            "truncate" => Ok(LengthPolicy::Truncate),
+            _ => Err(PyValueError::new_err(format!(
+                "Invalid length policy: {}",
+                s
+            ))),
+        }
+    }
+}
+
+/// The maximum lengths in bytes of the fields of a testcase.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldLimits {
+    pub name: usize,
+    pub classname: usize,
+    pub testsuite: usize,
+    pub file: usize,
+    pub computed_name: usize,
+    /// Longer failure messages are truncated from the middle, regardless of the
+    /// [`LengthPolicy`]. They are not limited by default.
+    pub failure_message: Option<usize>,
+}
+
+impl Default for FieldLimits {
+    fn default() -> Self {
+        Self {
+            name: DEFAULT_MAX_LENGTH,
+            classname: DEFAULT_MAX_LENGTH,
+            testsuite: DEFAULT_MAX_LENGTH,
+            file: DEFAULT_MAX_LENGTH,
+            computed_name: DEFAULT_MAX_LENGTH,
+            failure_message: None,
+        }
+    }
+}
+
+impl<'py> FromPyObject<'py> for FieldLimits {
+    fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
+        let dict = ob.downcast::<PyDict>()?;
+        let mut limits = FieldLimits::default();
+
+        for (key, limit) in [
+            ("name", &mut limits.name),
+            ("classname", &mut limits.classname),
+            ("testsuite", &mut limits.testsuite),
+            ("file", &mut limits.file),
+            ("computed_name", &mut limits.computed_name),
+        ] {
+            if let Some(value) = dict.get_item(key)? {
+                *limit = value.extract()?;
+            }
+        }
+        if let Some(failure_message) = dict.get_item("failure_message")? {
+            limits.failure_message = failure_message.extract()?;
+        }
+
+        Ok(limits)
+    }
+}
+
+/// Options which influence how test result files are being parsed.
+///
+/// From Python, these are passed as a `dict`, in which all the keys are optional.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseOptions {
+    /// The maximum length in bytes of the `system-out` and `system-err` captured per testcase.
+    ///
+    /// Longer output is truncated from the middle, keeping its beginning and end.
+    pub max_output_length: usize,
+    /// If set, only the `property` elements with a name matching one of these patterns
+    /// are collected.
+    ///
+    /// A pattern matches a property name exactly, or by prefix if it ends in `*`.
+    pub property_allowlist: Option<Vec<String>>,
+    /// The `property` elements with a name matching one of these patterns are skipped.
+    pub property_denylist: Vec<String>,
+    /// Whether to keep the testcases parsed before an error in a truncated or
+    /// malformed file, instead of failing the whole upload.
+    pub lenient: bool,
+    /// The maximum lengths of the fields of a testcase.
+    pub field_limits: FieldLimits,
+    /// What to do with testcases exceeding the `field_limits`.
+    pub length_policy: LengthPolicy,
+    /// Custom rules for the computed names, replacing the built-in naming schemes.
+    pub naming_rules: NamingRules,
+    /// The directories containing the root packages of pytest tests, like `src`.
+    ///
+    /// These are preferred when the module of a testcase matches several files of
+    /// the upload `network`.
+    pub network_roots: Vec<String>,
+}
+
+impl ParseOptions {
+    /// Whether the `property` with the given name should be collected.
+    pub fn is_property_allowed(&self, name: &str) -> bool {
+        let matches = |pattern: &String| match pattern.strip_suffix('*') {
+            Some(prefix) => name.starts_with(prefix),
+            None => name == pattern,
+        };
+
+        let allowed = match &self.property_allowlist {
+            Some(allowlist) => allowlist.iter().any(matches),
+            None => true,
+        };
+        allowed && !self.property_denylist.iter().any(matches)
+    }
+
+    /// Checks `value` against the `max_length` of its field, truncating it if the
+    /// [`LengthPolicy`] says so.
+    pub fn limit_length(
+        &self,
+        value: String,
+        max_length: usize,
+    ) -> anyhow::Result<ValidatedString> {
+        match self.length_policy {
+            LengthPolicy::Skip => ValidatedString::from_string_with_limit(value, max_length),
+            LengthPolicy::Truncate => Ok(ValidatedString::truncated(value, max_length)),
+        }
+    }
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            max_output_length: DEFAULT_MAX_OUTPUT_LENGTH,
+            property_allowlist: None,
+            property_denylist: vec![],
+            lenient: false,
+            field_limits: FieldLimits::default(),
+            length_policy: LengthPolicy::default(),
+            naming_rules: NamingRules::default(),
+            network_roots: vec![],
+        }
+    }
+}
+
+impl<'py> FromPyObject<'py> for ParseOptions {
+    fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
+        let dict = ob.downcast::<PyDict>()?;
+        let mut options = ParseOptions::default();
+
+        if let Some(max_output_length) = dict.get_item("max_output_length")? {
+            options.max_output_length = max_output_length.extract()?;
+        }
+        if let Some(property_allowlist) = dict.get_item("property_allowlist")? {
+            options.property_allowlist = property_allowlist.extract()?;
+        }
+        if let Some(property_denylist) = dict.get_item("property_denylist")? {
+            options.property_denylist = property_denylist.extract()?;
+        }
+        if let Some(lenient) = dict.get_item("lenient")? {
+            options.lenient = lenient.extract()?;
+        }
+        if let Some(field_limits) = dict.get_item("field_limits")? {
+            options.field_limits = field_limits.extract()?;
+        }
+        if let Some(length_policy) = dict.get_item("length_policy")? {
+            options.length_policy = length_policy.extract()?;
+        }
+        if let Some(naming_rules) = dict.get_item("naming_rules")? {
+            options.naming_rules = naming_rules.extract()?;
+        }
+        if let Some(network_roots) = dict.get_item("network_roots")? {
+            options.network_roots = network_roots.extract()?;
+        }
+
+        Ok(options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_property_lists() {
+        let mut options = ParseOptions::default();
+        assert!(options.is_property_allowed("cmake_labels"));
+
+        options.property_denylist = vec!["evals.scores.*".into(), "owner".into()];
+        assert!(options.is_property_allowed("evals.item.id"));
+        assert!(!options.is_property_allowed("evals.scores.isUseful.value"));
+        assert!(!options.is_property_allowed("owner"));
+        assert!(options.is_property_allowed("owners"));
+
+        options.property_allowlist = Some(vec!["evals.*".into()]);
+        assert!(options.is_property_allowed("evals.item.id"));
+        assert!(!options.is_property_allowed("evals.scores.isUseful.value"));
+        assert!(!options.is_property_allowed("cmake_labels"));
+    }
+}
@@ -2,6 +2,7 @@ use anyhow::Context;
 
 use base64::prelude::*;
 use pyo3::prelude::*;
+use std::borrow::Cow;
 use std::collections::HashSet;
 use std::io::prelude::*;
 
@@ -10,7 +11,10 @@ use flate2::bufread::ZlibDecoder;
 use quick_xml::reader::Reader;
 use serde::Deserialize;
 
-use crate::junit::{get_position_info, use_reader};
+use crate::encoding::transcode_to_utf8;
+use crate::junit::{get_position_info, use_reader, ParsedJUnit};
+use crate::network::Network;
+use crate::options::ParseOptions;
 use crate::testrun::ParsingInfo;
 use crate::warning::WarningInfo;
 
@@ -51,7 +55,7 @@ fn serialize_to_legacy_format(readable_files: Vec<ReadableFile>) -> Vec<u8> {
 
 // the warnings should be ordered by location because they're pushed to the vec as we parse
 // so we can guarantee that warning[x].location >= warning[x - 1].location
-// implicitly tested by warnings-junit.xml
+// implicitly tested by warnings-junit.xml and duration-warnings.junit.xml
 fn format_warnings(input: &[u8], warnings: Vec<WarningInfo>, filename: &str) -> Vec<String> {
     let mut offset = 0;
     let mut result = Vec::new();
@@ -61,7 +65,7 @@ fn format_warnings(input: &[u8], warnings: Vec<WarningInfo>, filename: &str) ->
     for warning in warnings {
         for bytes in input_iter
             .by_ref()
-            .take((warning.location - offset) as usize)
+            .take(warning.location.saturating_sub(offset) as usize)
         {
             if *bytes == b'\n' {
                 line += 1;
@@ -70,7 +74,7 @@ fn format_warnings(input: &[u8], warnings: Vec<WarningInfo>, filename: &str) ->
                 col += 1;
             }
         }
-        offset += warning.location;
+        offset = offset.max(warning.location);
         result.push(format!(
             "{} at {}:{} in {}",
             warning.message, line, col, filename
@@ -80,11 +84,17 @@ fn format_warnings(input: &[u8], warnings: Vec<WarningInfo>, filename: &str) ->
 }
 
 #[pyfunction]
-#[pyo3(signature = (raw_upload_bytes))]
-pub fn parse_raw_upload(raw_upload_bytes: &[u8]) -> anyhow::Result<(Vec<ParsingInfo>, Vec<u8>)> {
+#[pyo3(signature = (raw_upload_bytes, options=None))]
+pub fn parse_raw_upload(
+    raw_upload_bytes: &[u8],
+    options: Option<ParseOptions>,
+) -> anyhow::Result<(Vec<ParsingInfo>, Vec<u8>)> {
+    let options = options.unwrap_or_default();
     let upload: RawTestResultUpload =
         serde_json::from_slice(raw_upload_bytes).context("Error deserializing json")?;
-    let network: Option<HashSet<String>> = upload.network;
+    let network = upload
+        .network
+        .map(|network| Network::new(network, &options.network_roots));
 
     let mut results: Vec<ParsingInfo> = Vec::with_capacity(upload.test_results_files.len());
     let mut readable_files: Vec<ReadableFile> = Vec::with_capacity(upload.test_results_files.len());
@@ -101,30 +111,57 @@ pub fn parse_raw_upload(raw_upload_bytes: &[u8]) -> anyhow::Result<(Vec<ParsingI
             .read_to_end(&mut decompressed_file_bytes)
             .context("Error decompressing file")?;
 
+        let (transcoded, invalid_encoding) = transcode_to_utf8(&decompressed_file_bytes);
+        let transcoded = match transcoded {
+            Cow::Owned(transcoded) => Some(transcoded),
+            Cow::Borrowed(_) => None,
+        };
+        let decompressed_file_bytes = transcoded.unwrap_or(decompressed_file_bytes);
+
         let mut reader = Reader::from_reader(decompressed_file_bytes.as_slice());
         reader.config_mut().trim_text(true);
-        let (framework, testruns, warnings) = use_reader(&mut reader, network.as_ref())
-            .with_context(|| {
-                let pos_conversion = reader.buffer_position().try_into();
-                match pos_conversion {
-                    Ok(pos) => {
-                        let (line, col) = get_position_info(&decompressed_file_bytes, pos);
-                        format!(
-                            "Error parsing JUnit XML in {} at {}:{}",
-                            file.filename, line, col
-                        )
-                    }
-                    Err(_) => format!("Error parsing JUnit XML in {}", file.filename),
+        let parsed = use_reader(&mut reader, network.as_ref(), &options);
+        let error_context = || {
+            let pos_conversion = reader.buffer_position().try_into();
+            match pos_conversion {
+                Ok(pos) => {
+                    let (line, col) = get_position_info(&decompressed_file_bytes, pos);
+                    format!(
+                        "Error parsing JUnit XML in {} at {}:{}",
+                        file.filename, line, col
+                    )
                 }
-            })?;
+                Err(_) => format!("Error parsing JUnit XML in {}", file.filename),
+            }
+        };
+        let ParsedJUnit {
+            framework,
+            frameworks,
+            testruns,
+            mut warnings,
+            partial,
+        } = match parsed {
+            Ok(parsed) => parsed,
+            Err(err) => return Err(anyhow::Error::from(err).context(error_context())),
+        };
+
+        if let Some(encoding) = invalid_encoding {
+            let message = format!(
+                "File contains byte sequences which are invalid in its {} encoding, they were replaced with U+FFFD",
+                encoding
+            );
+            warnings.insert(0, WarningInfo::new(message, 0));
+        }
 
         let warning_strings: Vec<String> =
             format_warnings(&decompressed_file_bytes, warnings, &file.filename);
 
         let parsing_info = ParsingInfo {
             framework,
+            frameworks,
             testruns,
             warnings: warning_strings,
+            partial,
         };
         results.push(parsing_info);
 
@@ -148,7 +185,7 @@ mod tests {
     use std::io::Write;
 
     use super::*;
-    use insta::{assert_yaml_snapshot, glob};
+    use insta::{assert_snapshot, assert_yaml_snapshot, glob};
 
     fn file_into_bytes(filename: &str) -> Vec<u8> {
         let upload = std::fs::read(filename).unwrap();
@@ -168,7 +205,7 @@ mod tests {
     fn test_parse_raw_upload_success() {
         glob!("../tests", "*.xml", |path| {
             let upload_json = file_into_bytes(path.to_str().unwrap());
-            let result = parse_raw_upload(&upload_json);
+            let result = parse_raw_upload(&upload_json, None);
             match result {
                 Ok((results, _)) => assert_yaml_snapshot!(results),
                 Err(e) => {
@@ -177,4 +214,63 @@ mod tests {
             }
         });
     }
+
+    #[test]
+    fn test_parse_raw_upload_readable_file() {
+        let upload_json = file_into_bytes("tests/latin1.junit.xml");
+        let (_, readable_file) = parse_raw_upload(&upload_json, None).unwrap();
+        assert_snapshot!(String::from_utf8(readable_file).unwrap());
+    }
+
+    #[test]
+    fn test_parse_raw_upload_lenient_truncated() {
+        let upload_json = file_into_bytes("tests/truncated.junit.xml");
+        let options = ParseOptions {
+            lenient: true,
+            ..Default::default()
+        };
+        let (results, _) = parse_raw_upload(&upload_json, Some(options)).unwrap();
+        assert_yaml_snapshot!(results);
+    }
+
+    #[test]
+    fn test_parse_raw_upload_lenient() {
+        let encode = |data: &[u8]| {
+            let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(data).unwrap();
+            BASE64_STANDARD.encode(encoder.finish().unwrap())
+        };
+        let truncated = br#"<testsuites>
+            <testsuite name="suite">
+                <testcase name="first" classname="a" />
+                <testcase name="second" classname="a"><failure message="boom" /></testcase>
+                <testcase name="third" classname="a"><failure mess"#;
+        let complete = std::fs::read("tests/junit.xml").unwrap();
+        let upload_json = format!(
+            r#"{{"test_results_files": [{{"filename": "truncated.xml", "data": "{}"}}, {{"filename": "junit.xml", "data": "{}"}}]}}"#,
+            encode(truncated),
+            encode(&complete),
+        );
+
+        let err = parse_raw_upload(upload_json.as_bytes(), None).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Error parsing JUnit XML in truncated.xml at 5:67"
+        );
+
+        let options = ParseOptions {
+            lenient: true,
+            ..Default::default()
+        };
+        let (results, _) = parse_raw_upload(upload_json.as_bytes(), Some(options)).unwrap();
+
+        assert!(results[0].partial);
+        let names: Vec<_> = results[0].testruns.iter().map(|t| &*t.name).collect();
+        assert_eq!(names, ["first", "second"]);
+        assert_eq!(results[0].warnings.len(), 1);
+        assert!(results[0].warnings[0].starts_with("File was only partially parsed"));
+
+        assert!(!results[1].partial);
+        assert_eq!(results[1].testruns.len(), 3);
+    }
 }
@@ -6,15 +6,88 @@ use serde_json::Value;
 
 use crate::validated_string::ValidatedString;
 
-static FRAMEWORKS: [(&str, Framework); 4] = [
+static FRAMEWORKS: [(&str, Framework); 15] = [
     ("pytest", Framework::Pytest),
     ("vitest", Framework::Vitest),
     ("jest", Framework::Jest),
     ("phpunit", Framework::PHPUnit),
+    ("ctest", Framework::CTest),
+    ("rspec", Framework::RSpec),
+    ("minitest", Framework::Minitest),
+    ("cucumber", Framework::Cucumber),
+    ("testng", Framework::TestNG),
+    ("kotest", Framework::Kotest),
+    ("spock", Framework::Spock),
+    ("googletest", Framework::GoogleTest),
+    ("gtest", Framework::GoogleTest),
+    ("catch2", Framework::Catch2),
+    // this is checked last, as other frameworks also write "JUnit" reports
+    ("junit", Framework::JUnit),
 ];
 
-static EXTENSIONS: [(&str, Framework); 2] =
-    [(".py", Framework::Pytest), (".php", Framework::PHPUnit)];
+static EXTENSIONS: [(&str, Framework); 7] = [
+    (".py", Framework::Pytest),
+    (".php", Framework::PHPUnit),
+    (".rb", Framework::RSpec),
+    (".feature", Framework::Cucumber),
+    (".java", Framework::JUnit),
+    // Kotest is only told apart from JUnit tests written in Kotlin by its stack traces
+    (".kt", Framework::JUnit),
+    (".groovy", Framework::Spock),
+];
+
+/// Packages which show up in the stack traces of failures of JVM test frameworks,
+/// which all run `.java`, `.kt` or `.groovy` files.
+static STACK_TRACE_PACKAGES: [(&str, Framework); 4] = [
+    ("org.testng.", Framework::TestNG),
+    ("io.kotest.", Framework::Kotest),
+    ("org.spockframework.", Framework::Spock),
+    ("org.junit.", Framework::JUnit),
+];
+
+/// The lowercased system names which CTest uses as the prefix of its testsuite names,
+/// which look like `Linux-c++` or `Windows-MSVC`.
+static CTEST_SYSTEM_NAMES: [&str; 6] = ["linux", "darwin", "windows", "freebsd", "cygwin", "msys"];
+
+/// The lowercased names of the compilers which CTest uses as the suffix of its
+/// testsuite names.
+static CTEST_COMPILER_NAMES: [&str; 12] = [
+    "c++", "cc", "g++", "gcc", "clang", "clang++", "cl", "msvc", "icc", "icpc", "icx", "icpx",
+];
+
+fn check_ctest_build_name(testsuite: &str) -> bool {
+    testsuite.split_once('-').is_some_and(|(system, compiler)| {
+        let compiler = compiler.to_lowercase();
+        let compiler = compiler.trim_end_matches(".exe");
+        // compilers can be suffixed with their version, like `g++-13`
+        let compiler = match compiler.rsplit_once('-') {
+            Some((name, version)) if version.chars().all(|c| c.is_ascii_digit() || c == '.') => {
+                name
+            }
+            _ => compiler,
+        };
+        CTEST_SYSTEM_NAMES.contains(&system.to_lowercase().as_str())
+            && CTEST_COMPILER_NAMES.contains(&compiler)
+    })
+}
+
+/// Minitest test methods have to start with `test_`, whereas RSpec examples are
+/// named by their description.
+fn check_minitest_name(name: &str) -> bool {
+    name.starts_with("test_")
+}
+
+/// `rspec_junit_formatter` writes the path of the spec file as the classname, like
+/// `spec.models.user_spec`, and spec files have to end with `_spec.rb`.
+fn check_rspec_classname(classname: &str) -> bool {
+    classname.ends_with("_spec")
+}
+
+/// Catch2 writes the name of the test binary and the class of a test as its classname,
+/// using `global` for the tests which are not defined on a class.
+fn check_catch2_classname(classname: &str) -> bool {
+    classname.ends_with(".global")
+}
 
 fn check_substring_before_word_boundary(string: &str, substring: &str) -> bool {
     if let Some((_, suffix)) = string.to_lowercase().split_once(substring) {
@@ -80,6 +153,17 @@ pub enum Framework {
     Vitest,
     Jest,
     PHPUnit,
+    CTest,
+    RSpec,
+    Minitest,
+    Cucumber,
+    /// JUnit 4 and 5, as reported by Maven Surefire or Gradle.
+    JUnit,
+    TestNG,
+    Kotest,
+    Spock,
+    GoogleTest,
+    Catch2,
 }
 
 impl<'py> IntoPyObject<'py> for Framework {
@@ -93,6 +177,16 @@ impl<'py> IntoPyObject<'py> for Framework {
             Framework::Vitest => Ok("Vitest".into_pyobject(py)?),
             Framework::Jest => Ok("Jest".into_pyobject(py)?),
             Framework::PHPUnit => Ok("PHPUnit".into_pyobject(py)?),
+            Framework::CTest => Ok("CTest".into_pyobject(py)?),
+            Framework::RSpec => Ok("RSpec".into_pyobject(py)?),
+            Framework::Minitest => Ok("Minitest".into_pyobject(py)?),
+            Framework::Cucumber => Ok("Cucumber".into_pyobject(py)?),
+            Framework::JUnit => Ok("JUnit".into_pyobject(py)?),
+            Framework::TestNG => Ok("TestNG".into_pyobject(py)?),
+            Framework::Kotest => Ok("Kotest".into_pyobject(py)?),
+            Framework::Spock => Ok("Spock".into_pyobject(py)?),
+            Framework::GoogleTest => Ok("GoogleTest".into_pyobject(py)?),
+            Framework::Catch2 => Ok("Catch2".into_pyobject(py)?),
         }
     }
 }
@@ -105,8 +199,18 @@ impl<'py> FromPyObject<'py> for Framework {
             "Vitest" => Ok(Framework::Vitest),
             "Jest" => Ok(Framework::Jest),
             "PHPUnit" => Ok(Framework::PHPUnit),
+            "CTest" => Ok(Framework::CTest),
+            "RSpec" => Ok(Framework::RSpec),
+            "Minitest" => Ok(Framework::Minitest),
+            "Cucumber" => Ok(Framework::Cucumber),
+            "JUnit" => Ok(Framework::JUnit),
+            "TestNG" => Ok(Framework::TestNG),
+            "Kotest" => Ok(Framework::Kotest),
+            "Spock" => Ok(Framework::Spock),
+            "GoogleTest" => Ok(Framework::GoogleTest),
+            "Catch2" => Ok(Framework::Catch2),
             _ => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
-                "Invalid outcome: {}",
+                "Invalid framework: {}",
                 s
             ))),
         }
@@ -150,6 +254,23 @@ impl<'py> FromPyObject<'py> for PropertiesValue {
     }
 }
 
+/// A single `failure` or `error` reported for a testcase.
+///
+/// This is also used for the failed attempts of a rerun testcase, which are reported
+/// by Maven Surefire and Gradle as `flakyFailure`, `flakyError`, `rerunFailure` and
+/// `rerunError` elements.
+#[derive(IntoPyObject, FromPyObject, Clone, Debug, Serialize, PartialEq)]
+pub struct TestFailure {
+    #[pyo3(item)]
+    pub outcome: Outcome,
+    #[pyo3(item)]
+    pub message: Option<String>,
+    #[pyo3(item, default)]
+    pub failure_type: Option<String>,
+    #[pyo3(item, default)]
+    pub details: Option<String>,
+}
+
 // i can't seem to get  pyo3(from_item_all) to work when IntoPyObject is also being derived
 #[derive(IntoPyObject, FromPyObject, Clone, Debug, Serialize, PartialEq)]
 pub struct Testrun {
@@ -163,54 +284,166 @@ pub struct Testrun {
     pub outcome: Outcome,
     #[pyo3(item)]
     pub testsuite: ValidatedString,
+    /// The rendered failure message, combining all the `failures` of the testcase.
     #[pyo3(item)]
     pub failure_message: Option<String>,
+    /// All the `failure` and `error` elements of the testcase, in order.
+    #[pyo3(item, default)]
+    pub failures: Vec<TestFailure>,
+    /// The `message` attribute of the first `failure` or `error` element, usually a short summary.
+    #[pyo3(item, default)]
+    pub failure_summary: Option<String>,
+    /// The `type` attribute of the first `failure` or `error` element, usually an exception type.
+    #[pyo3(item, default)]
+    pub failure_type: Option<String>,
+    /// The text body of the first `failure` or `error` element, usually a stack trace.
+    #[pyo3(item, default)]
+    pub failure_details: Option<String>,
     #[pyo3(item)]
     pub filename: Option<ValidatedString>,
     #[pyo3(item)]
     pub build_url: Option<String>,
     #[pyo3(item)]
     pub computed_name: ValidatedString,
+    /// The `computed_name` of the base test of a parameterized test, without its parameters.
+    #[pyo3(item, default)]
+    pub base_computed_name: Option<ValidatedString>,
+    /// The parameters of a parameterized test, as they appear in its name.
+    #[pyo3(item, default)]
+    pub parameters: Option<String>,
+    /// The titles of the `describe` blocks of a Jest or Vitest test, followed by its own title.
+    #[pyo3(item, default)]
+    pub hierarchy: Vec<String>,
+    /// The framework whose naming rules were used for the `computed_name`.
+    #[pyo3(item, default)]
+    pub framework: Option<Framework>,
     #[pyo3(item)]
     pub properties: PropertiesValue,
+    /// The captured `system-out` of the testcase, possibly truncated.
+    #[pyo3(item, default)]
+    pub system_out: Option<String>,
+    /// The captured `system-err` of the testcase, possibly truncated.
+    #[pyo3(item, default)]
+    pub system_err: Option<String>,
+    /// The failed attempts of a testcase that was rerun, in order.
+    #[pyo3(item, default)]
+    pub retries: Vec<TestFailure>,
+    /// The total number of times the testcase was run, including the final attempt.
+    #[pyo3(item, default = 1)]
+    pub attempts: u32,
+    /// Whether the testcase passed only after failing on some of its attempts.
+    #[pyo3(item, default)]
+    pub flaky: bool,
+}
+
+impl Default for Testrun {
+    /// A passing testrun, run once, with all the other fields left empty.
+    fn default() -> Self {
+        Self {
+            name: ValidatedString::default(),
+            classname: ValidatedString::default(),
+            duration: None,
+            outcome: Outcome::Pass,
+            testsuite: ValidatedString::default(),
+            failure_message: None,
+            failures: vec![],
+            failure_summary: None,
+            failure_type: None,
+            failure_details: None,
+            filename: None,
+            build_url: None,
+            computed_name: ValidatedString::default(),
+            base_computed_name: None,
+            parameters: None,
+            hierarchy: vec![],
+            framework: None,
+            properties: PropertiesValue(None),
+            system_out: None,
+            system_err: None,
+            retries: vec![],
+            attempts: 1,
+            flaky: false,
+        }
+    }
 }
 
 impl Testrun {
-    pub fn framework(&self) -> Option<Framework> {
+    /// Detects the framework of this testrun from its own attributes and failure message.
+    pub fn detect_framework(&self) -> Option<Framework> {
         for (name, framework) in FRAMEWORKS {
             if check_substring_before_word_boundary(&self.testsuite, name) {
                 return Some(framework);
             }
         }
 
-        for (extension, framework) in EXTENSIONS {
-            if check_substring_before_word_boundary(&self.classname, extension)
-                || check_substring_before_word_boundary(&self.name, extension)
-            {
-                return Some(framework);
-            }
+        if check_ctest_build_name(&self.testsuite) {
+            return Some(Framework::CTest);
+        }
+
+        if check_catch2_classname(&self.classname) {
+            return Some(Framework::Catch2);
+        }
 
-            if let Some(message) = &self.failure_message {
-                if check_substring_before_word_boundary(message, extension) {
+        if let Some(message) = &self.failure_message {
+            for (package, framework) in STACK_TRACE_PACKAGES {
+                if message.contains(package) {
                     return Some(framework);
                 }
             }
+        }
 
-            if let Some(filename) = &self.filename {
-                if check_substring_before_word_boundary(filename, extension) {
-                    return Some(framework);
+        for (extension, framework) in EXTENSIONS {
+            if !self.mentions_extension(extension) {
+                continue;
+            }
+            // both RSpec and Minitest run `.rb` files, so they are told apart by the
+            // shape of their names, and other `.rb` files are left alone
+            if framework == Framework::RSpec {
+                if check_minitest_name(&self.name) {
+                    return Some(Framework::Minitest);
+                }
+                if check_rspec_classname(&self.classname) {
+                    return Some(Framework::RSpec);
                 }
+                continue;
             }
+            return Some(framework);
         }
         None
     }
+
+    fn mentions_extension(&self, extension: &str) -> bool {
+        if check_substring_before_word_boundary(&self.classname, extension)
+            || check_substring_before_word_boundary(&self.name, extension)
+        {
+            return true;
+        }
+
+        if let Some(message) = &self.failure_message {
+            if check_substring_before_word_boundary(message, extension) {
+                return true;
+            }
+        }
+
+        if let Some(filename) = &self.filename {
+            if check_substring_before_word_boundary(filename, extension) {
+                return true;
+            }
+        }
+        false
+    }
 }
 
 #[derive(Clone, Debug, Serialize, IntoPyObject)]
 pub struct ParsingInfo {
+    /// The framework used by most of the `testruns`.
     pub framework: Option<Framework>,
+    /// All the frameworks used by the `testruns`, in order of their first appearance.
+    pub frameworks: Vec<Framework>,
     pub testruns: Vec<Testrun>,
     pub warnings: Vec<String>,
+    /// Whether the file is truncated or malformed, and was only partially parsed.
+    pub partial: bool,
 }
 
 #[cfg(test)]
@@ -237,103 +470,125 @@ mod tests {
     #[test]
     fn test_detect_framework_testsuite_name() {
         let t = Testrun {
-            classname: ValidatedString::default(),
-            name: ValidatedString::default(),
-            duration: None,
-            outcome: Outcome::Pass,
             testsuite: "pytest".try_into().unwrap(),
-            failure_message: None,
-            filename: None,
-            build_url: None,
-            computed_name: ValidatedString::default(),
-            properties: PropertiesValue(None),
+            ..Default::default()
         };
-        assert_eq!(t.framework(), Some(Framework::Pytest))
+        assert_eq!(t.detect_framework(), Some(Framework::Pytest))
+    }
+
+    #[test]
+    fn test_detect_framework_ctest_build_name() {
+        assert!(check_ctest_build_name("Linux-c++"));
+        assert!(check_ctest_build_name("Windows-MSVC"));
+        assert!(check_ctest_build_name("Darwin-clang++-17"));
+        assert!(check_ctest_build_name("Windows-cl.exe"));
+        assert!(!check_ctest_build_name("Linux"));
+        assert!(!check_ctest_build_name("my-tests"));
+        assert!(!check_ctest_build_name("Windows-latest"));
+        assert!(!check_ctest_build_name("Linux-"));
     }
 
     #[test]
     fn test_detect_framework_filenames() {
         let t = Testrun {
-            classname: ValidatedString::default(),
-            name: ValidatedString::default(),
-            duration: None,
-            outcome: Outcome::Pass,
-            testsuite: ValidatedString::default(),
-            failure_message: None,
             filename: Some(".py".try_into().unwrap()),
-            build_url: None,
-            computed_name: ValidatedString::default(),
-            properties: PropertiesValue(None),
+            ..Default::default()
         };
-        assert_eq!(t.framework(), Some(Framework::Pytest))
+        assert_eq!(t.detect_framework(), Some(Framework::Pytest))
     }
 
     #[test]
     fn test_detect_framework_example_classname() {
         let t = Testrun {
             classname: ".py".try_into().unwrap(),
-            name: ValidatedString::default(),
-            duration: None,
-            outcome: Outcome::Pass,
-            testsuite: ValidatedString::default(),
-            failure_message: None,
-            filename: None,
-            build_url: None,
-            computed_name: ValidatedString::default(),
-            properties: PropertiesValue(None),
+            ..Default::default()
         };
-        assert_eq!(t.framework(), Some(Framework::Pytest))
+        assert_eq!(t.detect_framework(), Some(Framework::Pytest))
     }
 
     #[test]
     fn test_detect_framework_example_name() {
         let t = Testrun {
-            classname: ValidatedString::default(),
             name: ".py".try_into().unwrap(),
-            duration: None,
-            outcome: Outcome::Pass,
-            testsuite: ValidatedString::default(),
-            failure_message: None,
-            filename: None,
-            build_url: None,
-            computed_name: ValidatedString::default(),
-            properties: PropertiesValue(None),
+            ..Default::default()
         };
-        assert_eq!(t.framework(), Some(Framework::Pytest))
+        assert_eq!(t.detect_framework(), Some(Framework::Pytest))
     }
 
     #[test]
     fn test_detect_framework_failure_messages() {
         let t = Testrun {
-            classname: ValidatedString::default(),
-            name: ValidatedString::default(),
-            duration: None,
-            outcome: Outcome::Pass,
-            testsuite: ValidatedString::default(),
             failure_message: Some(".py".to_string()),
-            filename: None,
-            build_url: None,
-            computed_name: ValidatedString::default(),
-            properties: PropertiesValue(None),
+            ..Default::default()
         };
-        assert_eq!(t.framework(), Some(Framework::Pytest))
+        assert_eq!(t.detect_framework(), Some(Framework::Pytest))
+    }
+
+    #[test]
+    fn test_detect_framework_ruby_and_cucumber() {
+        let mut t = Testrun {
+            classname: "spec.models.user_spec".try_into().unwrap(),
+            name: "User validates the email".try_into().unwrap(),
+            filename: Some("./spec/models/user_spec.rb".try_into().unwrap()),
+            ..Default::default()
+        };
+        assert_eq!(t.detect_framework(), Some(Framework::RSpec));
+
+        t.classname = "UserTest".try_into().unwrap();
+        t.name = "test_validates_the_email".try_into().unwrap();
+        t.filename = Some("test/models/user_test.rb".try_into().unwrap());
+        assert_eq!(t.detect_framework(), Some(Framework::Minitest));
+
+        // other tests of `.rb` files are neither
+        t.classname = "test.test".try_into().unwrap();
+        t.name = "test.test works".try_into().unwrap();
+        t.filename = Some("./test.rb".try_into().unwrap());
+        assert_eq!(t.detect_framework(), None);
+
+        t.classname = "Addition".try_into().unwrap();
+        t.name = "Add two numbers".try_into().unwrap();
+        t.filename = Some("features/addition.feature".try_into().unwrap());
+        assert_eq!(t.detect_framework(), Some(Framework::Cucumber));
+
+        t.filename = None;
+        t.testsuite = "io.cucumber.core.plugin.JUnitFormatter".try_into().unwrap();
+        assert_eq!(t.detect_framework(), Some(Framework::Cucumber));
+    }
+
+    #[test]
+    fn test_detect_framework_jvm() {
+        let mut t = Testrun {
+            classname: "com.example.CalculatorTest".try_into().unwrap(),
+            name: "shouldAdd".try_into().unwrap(),
+            outcome: Outcome::Failure,
+            testsuite: "com.example.CalculatorTest".try_into().unwrap(),
+            failure_message: Some(
+                "java.lang.AssertionError: expected [3] but found [2]
+\tat org.testng.Assert.fail(Assert.java:110)
+\tat com.example.CalculatorTest.shouldAdd(CalculatorTest.java:12)"
+                    .to_string(),
+            ),
+            ..Default::default()
+        };
+        assert_eq!(t.detect_framework(), Some(Framework::TestNG));
+
+        t.failure_message =
+            Some("\tat com.example.CalculatorTest.shouldAdd(CalculatorTest.java:12)".to_string());
+        assert_eq!(t.detect_framework(), Some(Framework::JUnit));
+
+        t.failure_message = None;
+        t.filename = Some("src/test/groovy/CalculatorSpec.groovy".try_into().unwrap());
+        assert_eq!(t.detect_framework(), Some(Framework::Spock));
     }
 
     #[test]
     fn test_detect_build_url() {
         let t = Testrun {
-            classname: ValidatedString::default(),
-            name: ValidatedString::default(),
-            duration: None,
-            outcome: Outcome::Pass,
-            testsuite: ValidatedString::default(),
             failure_message: Some(".py".to_string()),
-            filename: None,
             build_url: Some("https://example.com/build_url".to_string()),
-            computed_name: ValidatedString::default(),
-            properties: PropertiesValue(None),
+            ..Default::default()
         };
-        assert_eq!(t.framework(), Some(Framework::Pytest))
+        assert_eq!(t.detect_framework(), Some(Framework::Pytest))
     }
 
     #[test]
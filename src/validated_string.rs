@@ -4,7 +4,13 @@ use anyhow::{Context, Result};
 use pyo3::{FromPyObject, IntoPyObject};
 use serde::{Deserialize, Serialize};
 
-// String that is validated to be less than 1000 characters
+/// The default maximum length of a [`ValidatedString`], in bytes.
+pub const DEFAULT_MAX_LENGTH: usize = 1000;
+
+/// The length of the hash suffix of a [`ValidatedString::truncated`] string.
+const HASH_SUFFIX_LENGTH: usize = 17;
+
+// String that is validated to be at most `DEFAULT_MAX_LENGTH`, or a custom limit of bytes
 
 #[derive(
     Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize, FromPyObject, IntoPyObject,
@@ -17,11 +23,43 @@ pub struct ValidatedString {
 
 impl ValidatedString {
     pub fn from_string(value: String) -> Result<Self> {
-        if value.len() > 1000 {
+        Self::from_string_with_limit(value, DEFAULT_MAX_LENGTH)
+    }
+
+    pub fn from_string_with_limit(value: String, max_length: usize) -> Result<Self> {
+        if value.len() > max_length {
             anyhow::bail!("string is too long");
         }
         Ok(Self { value })
     }
+
+    /// Truncates `value` to at most `max_length` bytes.
+    ///
+    /// A truncated string ends in `~` followed by a hash of the complete `value`,
+    /// so that long strings which only differ at their end remain distinct.
+    pub fn truncated(mut value: String, max_length: usize) -> Self {
+        if value.len() <= max_length {
+            return Self { value };
+        }
+
+        let suffix = format!("~{:016x}", fnv1a(value.as_bytes()));
+        let mut prefix_end = max_length.saturating_sub(HASH_SUFFIX_LENGTH);
+        while !value.is_char_boundary(prefix_end) {
+            prefix_end -= 1;
+        }
+        value.truncate(prefix_end);
+        value.push_str(&suffix);
+        value.truncate(max_length);
+
+        Self { value }
+    }
+}
+
+/// The 64-bit FNV-1a hash, which is stable across platforms and releases.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
 }
 
 impl Deref for ValidatedString {
@@ -47,3 +85,32 @@ impl TryFrom<&str> for ValidatedString {
         Self::from_string(value.to_string()).context("Error converting &str to ValidatedString")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limits() {
+        let long = "a".repeat(DEFAULT_MAX_LENGTH + 1);
+        assert!(ValidatedString::from_string(long.clone()).is_err());
+        assert!(ValidatedString::from_string_with_limit(long, DEFAULT_MAX_LENGTH + 1).is_ok());
+    }
+
+    #[test]
+    fn test_truncated() {
+        let short = ValidatedString::truncated("test_a".into(), 30);
+        assert_eq!(&*short, "test_a");
+
+        let a = ValidatedString::truncated(format!("test[{}-a]", "x".repeat(50)), 30);
+        let b = ValidatedString::truncated(format!("test[{}-b]", "x".repeat(50)), 30);
+        assert_eq!(a.len(), 30);
+        assert!(a.starts_with("test[xxxxxxxx~"));
+        assert_ne!(a, b);
+
+        // the truncation does not split multi-byte characters
+        let c = ValidatedString::truncated("ä".repeat(20), 30);
+        assert_eq!(c.len(), 29);
+        assert!(c.starts_with("ääääää~"));
+    }
+}
@@ -1,5 +1,28 @@
 from typing import Literal, TypedDict
 
+Framework = Literal[
+    "Pytest",
+    "Jest",
+    "Vitest",
+    "PHPUnit",
+    "CTest",
+    "RSpec",
+    "Minitest",
+    "Cucumber",
+    "JUnit",
+    "TestNG",
+    "Kotest",
+    "Spock",
+    "GoogleTest",
+    "Catch2",
+]
+
+class TestFailure(TypedDict):
+    outcome: Literal["failure", "error"]
+    message: str | None
+    failure_type: str | None
+    details: str | None
+
 class Testrun(TypedDict):
     name: str
     classname: str
@@ -7,13 +30,56 @@ class Testrun(TypedDict):
     outcome: Literal["pass", "failure", "skip", "error"]
     testsuite: str
     failure_message: str | None
+    failures: list[TestFailure]
+    failure_summary: str | None
+    failure_type: str | None
+    failure_details: str | None
     filename: str | None
     build_url: str | None
     computed_name: str
+    base_computed_name: str | None
+    parameters: str | None
+    hierarchy: list[str]
+    framework: Framework | None
+    system_out: str | None
+    system_err: str | None
+    retries: list[TestFailure]
+    attempts: int
+    flaky: bool
 
 class ParsingInfo(TypedDict):
-    framework: Literal["Pytest", "Jest", "Vitest", "PHPUnit"] | None
+    framework: Framework | None
+    frameworks: list[Framework]
     testruns: list[Testrun]
     warnings: list[str]
+    partial: bool
+
+class FieldLimits(TypedDict, total=False):
+    name: int
+    classname: int
+    testsuite: int
+    file: int
+    computed_name: int
+    failure_message: int | None
+
+class NameRewrite(TypedDict, total=False):
+    pattern: str
+    replacement: str
+
+class NamingRules(TypedDict, total=False):
+    templates: dict[Framework | Literal["default"], str]
+    rewrites: list[NameRewrite]
+
+class ParseOptions(TypedDict, total=False):
+    max_output_length: int
+    property_allowlist: list[str] | None
+    property_denylist: list[str]
+    lenient: bool
+    field_limits: FieldLimits
+    length_policy: Literal["skip", "truncate"]
+    naming_rules: NamingRules
+    network_roots: list[str]
 
-def parse_raw_upload(raw_upload_bytes: bytes) -> tuple[list[ParsingInfo], bytes]: ...
+def parse_raw_upload(
+    raw_upload_bytes: bytes, options: ParseOptions | None = None
+) -> tuple[list[ParsingInfo], bytes]: ...
@@ -55,4 +55,74 @@ def test_aggregation():
             "commits_where_fail":test.commits_where_fail,
             "last_duration":test.last_duration,# TODO
         }
-        print(test_dict)
\ No newline at end of file
+        print(test_dict)
+
+def test_aggregation_from_file(tmp_path):
+    with open("./tests/junit.xml", "br") as f:
+        junit_file = f.read()
+
+        raw_upload = {
+            "test_results_files": [
+                {
+                    "filename": "test_results.json",
+                    "data": base64.b64encode(zlib.compress(junit_file)).decode("utf-8"),
+                }
+            ]
+        }
+
+    parsed, _ = parse_raw_upload(json.dumps(raw_upload).encode("utf-8"))
+
+    now = int(datetime.now(timezone.utc).timestamp())
+
+    writer = BinaryFormatWriter()
+    writer.add_testruns(
+        timestamp=now,
+        commit_hash="e9fcd08652d091fa0c8d28e323c24fb0f4acf249",
+        flags=[],
+        testruns=parsed[0]["testruns"],
+    )
+
+    path = tmp_path / "aggregation.bin"
+    path.write_bytes(bytes(writer.serialize()))
+
+    from_file = AggregationReader.open(str(path), now).get_test_aggregates(0, 2)
+    from_bytes = AggregationReader(path.read_bytes(), now).get_test_aggregates(0, 2)
+    from_buffer = AggregationReader(memoryview(path.read_bytes()), now).get_test_aggregates(0, 2)
+
+    assert len(from_file) == len(parsed[0]["testruns"])
+    assert [test.name for test in from_file] == [test.name for test in from_bytes]
+    assert [test.name for test in from_file] == [test.name for test in from_buffer]
+
+
+def test_aggregation_copies_writable_buffer():
+    with open("./tests/junit.xml", "br") as f:
+        junit_file = f.read()
+
+        raw_upload = {
+            "test_results_files": [
+                {
+                    "filename": "test_results.json",
+                    "data": base64.b64encode(zlib.compress(junit_file)).decode("utf-8"),
+                }
+            ]
+        }
+
+    parsed, _ = parse_raw_upload(json.dumps(raw_upload).encode("utf-8"))
+
+    now = int(datetime.now(timezone.utc).timestamp())
+
+    writer = BinaryFormatWriter()
+    writer.add_testruns(
+        timestamp=now,
+        commit_hash="e9fcd08652d091fa0c8d28e323c24fb0f4acf249",
+        flags=[],
+        testruns=parsed[0]["testruns"],
+    )
+
+    buffer = bytearray(writer.serialize())
+    reader = AggregationReader(memoryview(buffer), now)
+    names = [test.name for test in reader.get_test_aggregates(0, 2)]
+
+    # the writable buffer was copied, so the reader is unaffected by changes to it
+    buffer[:] = bytes(len(buffer))
+    assert [test.name for test in reader.get_test_aggregates(0, 2)] == names
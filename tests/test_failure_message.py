@@ -171,6 +171,54 @@ def test_build_message():
 > </details>
 
 
+</details>
+
+:mega: Thoughts on this report? [Let Codecov know!](https://github.com/codecov/feedback/issues/304) | Powered by [Codecov](https://about.codecov.io/)"""
+
+
+def test_build_message_multiple_failures():
+    run = Run()
+    run.testsuite = "hello"
+    run.name = "test_name_run"
+    run.failure_message = "first failure\n\nsecond failure"
+    run.duration = 0.001
+    run.build_url = None
+    run.failures = [
+        {"outcome": "failure", "message": "summary", "details": "first failure"},
+        {"outcome": "error", "message": "second failure", "details": None},
+    ]
+
+    payload = TestRunsPayload()
+    payload.passed = 1
+    payload.failed = 2
+    payload.failures = [run]
+
+    res = build_message(payload)
+
+    assert res == """### :x: 2 Tests Failed:
+| Tests completed | Failed | Passed | Skipped |
+|---|---|---|---|
+| 3 | 2 | 1 | 0 |
+<details><summary>View the top 1 failed tests by shortest run time</summary>
+
+> 
+> ```
+> test_name_run
+> ```
+> 
+> <details><summary>Stack Traces | 0.001s run time</summary>
+> 
+> > ```
+> > first failure
+> > ```
+> >
+> > ```
+> > second failure
+> > ```
+> 
+> </details>
+
+
 </details>
 
 :mega: Thoughts on this report? [Let Codecov know!](https://github.com/codecov/feedback/issues/304) | Powered by [Codecov](https://about.codecov.io/)"""